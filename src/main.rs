@@ -1,19 +1,29 @@
-// TODO: Add pause unpause option to scraping.
-//
 use crossterm::event::{
     self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
     MouseEvent, MouseEventKind,
 }; // Added Mouse types
 use crossterm::execute;
 use crossterm::terminal::{
-    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+    EnterAlternateScreen, LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
 };
-use fantoccini::{Client, Locator};
+use fantoccini::{
+    Client, ClientBuilder, Locator,
+    error::{CmdError, NewSessionError},
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
-use std::io;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, mpsc};
 use tui::{
     Frame, Terminal,
@@ -21,339 +31,6430 @@ use tui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{BarChart, Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
+use unicode_width::UnicodeWidthStr;
 use url::Url;
 
 // --- Constants ---
 const WEBDRIVER_URL: &str = "http://localhost:4444";
 const CRAWLER_CHANNEL_BUFFER: usize = 100;
 const EVENT_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+const CRAWL_DELAY: Duration = Duration::from_millis(50); // Pause between pages
+const SELECTOR_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 10;
 const SCROLL_LINES: u16 = 3; // Adjusted scroll speed slightly
+const CLEAN_VIEW_TAB_WIDTH: usize = 4;
+const SEARCH_HISTORY_LEN: usize = 20; // Queries kept for the Ctrl+R picker
+const MAX_RENDERED_LINE_CHARS: usize = 1000; // Per line with wrapping off; wider than any terminal
+const MIN_BODY_CHARS_STEP: usize = 100; // Change per +/- press of the body length filter
+const BOILERPLATE_MIN_PAGES: usize = 3; // --dim-boilerplate dims lines on at least this many pages
+const BOILERPLATE_SHARE: usize = 3; // and on at least 1 in this many of all pages
+// Following these tends to end the session the crawler is browsing with, or loops on a login form
+const DEFAULT_AUTH_SKIP_WORDS: &[&str] = &[
+    "logout", "log-out", "log_out", "signout", "sign-out", "sign_out", "login", "log-in", "log_in",
+    "signin", "sign-in", "sign_in",
+];
+// --check-encoding flags a body with at least this many U+FFFD, at least one per this many chars
+const ENCODING_MIN_REPLACEMENTS: usize = 3;
+const ENCODING_CHARS_PER_REPLACEMENT: usize = 1000;
+const DEFAULT_LIST_FORMAT: &str = "[{n}] {url}";
+// Email addresses, collected when --harvest is given no pattern
+const DEFAULT_HARVEST_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
 
-// --- Application State ---
+// --- Configuration ---
 
-struct AppState {
-    visited_urls: Vec<String>,
-    body_texts: HashMap<String, String>,
-    list_state: ListState,
-    search_input: String,
-    active_search_query: String,
-    is_searching: bool,
-    filtered_url_indices: Vec<usize>,
-    content_scroll: u16,
-    content_area: Rect, // Store the area/bounds of the content panel
+const USAGE: &str = "\
+Usage: crabcrawl [OPTIONS] [URL]
+
+Options:
+  --url <url>                Start crawling this URL right away instead of asking (same as passing URL)
+  --stdin                    Read the seed URL from the first line of stdin, e.g. from a pipe
+  --resume <file>            Browse a saved session instead of crawling
+  --continue-last            Browse the most recently saved session
+  --baseline <file>          Compare the crawl against a saved session (B): added, removed and changed pages
+  --profile <name>           Start with the options saved in a profile (K saves one); later options override it
+  --proxy <url>              Route browser traffic through a proxy (http://, https://, socks4://, socks5://)
+  --user-agent <ua>          Send this User-Agent instead of the browser's own
+  --user-agent-file <file>   Rotate through the User-Agents in this file, one per line: each WebDriver session takes the next
+  --set-terminal-title       Show crawl progress in the terminal window/tab title
+  --no-summary               Don't show the summary popup when a crawl finishes
+  --no-heartbeat             Don't blink a dot and count quiet seconds in the status bar while crawling
+  --follow-pagination        Crawl rel=next pages ahead of everything else in the queue
+  --spa-mode                 Experimental: also follow #/route links of single-page apps, each as its own page
+  --html-only                Skip pages the server didn't send as HTML (PDFs, images, plain text, ...)
+  --pin-top                  Keep the first page in the list selected while results stream in (toggle with P)
+  --collapse-repeats         Show runs of identical lines in the content once, with a count (toggle with z)
+  --dim-boilerplate          Dim content lines found on many pages, like navigation and footers (toggle with G)
+  --loop-threshold <n>       Stop following links from pages whose exact text was seen more than n times recently
+  --no-ascend                Only follow links at least as deep in the path as the seed URL
+  --subtree                  Only follow links under the seed URL's directory
+  --scope-domain <domain>    Follow links on this domain instead of the seed's; .domain also takes its subdomains
+  --max-path-depth <n>       Only follow links whose path has at most n segments (/a/b/c is 3)
+  --no-auth-skip             Also follow login/logout/sign-in style links
+  --auth-skip-words <list>   Comma-separated URL substrings to skip instead of the built-in auth list
+  --adaptive-delay           Wait between pages as long as pages have recently taken to load
+  --max-delay-ms <ms>        Longest wait --adaptive-delay will use (default 5000)
+  --shuffle                  Queue each page's links in random order
+  --seed <n>                 Seed for --shuffle, to repeat the same order
+  --deterministic            Queue each page's links sorted and crawl with one worker, for reproducible runs
+  --sort-query-params        Treat URLs that differ only in query parameter order as the same page
+  --print                    Print each page as a url<TAB>text line to stdout instead of the TUI (needs URL)
+  --cat                      Print every page's full text under a URL header to stdout, e.g. for a pager (needs URL)
+  --jsonld                   Extract JSON-LD (application/ld+json) structured data
+  --capture-html             Keep each page's HTML as well as its text, for copying with O
+  --link-attr <name>         Also follow links in this attribute, e.g. data-href or onclick (repeatable)
+  --anchor-text              Record the text of the links found on each page (shown in the links popup with l)
+  --sitemap-out <file>       Write a sitemap of the crawled pages when the crawl ends (.xml, otherwise plain text)
+  --clipboard <method>       How y/Y/c copy: auto (the default), tool (xclip and co), osc52 (via the terminal) or file
+  --compress                 Gzip every export and add .gz to its name (implied for export paths ending in .gz)
+  --output-fifo <path>       Stream each result as a line of JSON to a named pipe (or file) as it arrives
+  --output-fd <n>            Stream each result as a line of JSON to an already open file descriptor
+  --metrics-port <port>      Serve crawl metrics in the Prometheus text format on 127.0.0.1:<port>/metrics
+  --harvest <regex>          Pattern to collect matches of across all pages (M); defaults to email addresses
+  --max-pages-per-domain <n> Stop crawling a host once n of its pages have loaded
+  --max-requests <n>         Stop the crawl after n page loads, counting failed ones
+  --memory-cap-mb <n>        Keep at most n MB of page text, dropping pages per --eviction to stay under it
+  --eviction <policy>        Pages dropped first at the cap: oldest (the default), largest or fifo
+  --pause-every <n>          Pause after every n crawled pages for review; Space resumes
+  --sessions <n>             Open n WebDriver sessions and crawl with that many workers (default 1)
+  --match-context <n>        Lines shown around each match when only matching lines are shown (default 2)
+  --max-render-lines <n>     Lay out and highlight at most n content lines per frame, from the top of the view
+  --max-duration-secs <n>    Stop taking new pages from the queue after n seconds
+  --content-selector <css>   Take the text from these elements instead of the body (repeatable)
+  --fallback-selector <css>  Otherwise try these one at a time and take the first with text (repeatable)
+  --strip-scripts            Leave the text of <script>, <style> and <noscript> elements out of page text
+  --on-empty-body <action>   For pages without a <body>: placeholder (default), skip, retry or source
+  --wait-for-selector <css>  After loading a page, wait until this element exists before extracting
+  --wait-timeout-secs <n>    Longest to wait for --wait-for-selector (default 10)
+  --check-encoding           Flag pages whose text is full of U+FFFD replacement characters
+  --list-format <template>   URL list rows; placeholders {n} {url} {title} {len} {size} {depth} {status} {time} {crawled}
+  --highlight-color <color>  Color of search matches: a name like yellow or light-blue, or #rrggbb (default red)
+  --scroll-indicator <where> Content scroll position in the title (default), the bottom corner, or hidden
+  --display-url <form>       Show and export URLs as discovered (raw, the default) or normalized (no trailing slash)
+  --auto-select <when>       Select the first page when the list is empty (on-empty, the default), always, or never
+  --time-display <style>     Crawl times as relative (2m ago, the default) or absolute (ISO 8601) (toggle with t)
+  --min-body-chars <n>       Hide pages whose body text is shorter than n characters
+  --keep-matching <regex>    Only keep pages whose text matches; links on the others are still followed
+  --webdriver-retries <n>    Connection attempts before giving up on WebDriver (default 5)
+  --webdriver-retry-ms <ms>  Delay before the first retry, doubled after each attempt (default 500)
+  -h, --help                 Print this help";
+
+// Settings parsed from the command line, shared by every crawl in this run.
+#[derive(Clone)]
+struct Config {
+    proxy: Option<Url>,
+    user_agent: Option<String>, // Wins over --user-agent-file
+    user_agent_file: Option<std::path::PathBuf>,
+    user_agents: Vec<String>, // Read from user_agent_file
+    set_terminal_title: bool,
+    show_summary: bool,
+    heartbeat: bool,
+    webdriver_retries: u32,
+    webdriver_retry_interval: Duration,
+    min_body_chars: usize,
+    extract_json_ld: bool,
+    capture_html: bool,
+    anchor_text: bool,
+    link_attrs: Vec<String>, // Attributes besides href that hold links, for JS-driven navigation
+    follow_pagination: bool,
+    spa_mode: bool,
+    html_only: bool,
+    pin_selection_top: bool,
+    collapse_repeats: bool,
+    dim_boilerplate: bool,
+    seed_url: Option<Url>,
+    seed_from_stdin: bool,
+    print: bool,
+    cat: bool,
+    max_duration: Option<Duration>,
+    sort_query_params: bool,
+    harvest_pattern: Regex,
+    keep_matching: Option<Regex>, // Pages whose text doesn't match are crawled but not kept
+    auth_skip_words: Vec<String>, // Links containing any of these are never enqueued
+    no_ascend: bool,
+    scope_domain: Option<String>, // Replaces the seed's host as the crawl's domain; a leading dot takes subdomains too
+    subtree: bool,
+    max_path_depth: Option<usize>,
+    wait_for_selector: Option<String>,
+    content_selectors: Vec<String>, // Regions to take the text from instead of the whole body
+    fallback_selectors: Vec<String>, // Tried in order when none of those have text
+    strip_scripts: bool,
+    on_empty_body: EmptyBodyAction,
+    list_format: String, // Template for URL list rows, see format_list_item
+    check_encoding: bool,
+    wait_timeout: Duration,
+    highlight_color: Color,
+    scroll_indicator: ScrollIndicator,
+    time_display: TimeDisplay,
+    url_display: UrlDisplay,
+    auto_select: AutoSelect,
+    loop_threshold: Option<usize>,
+    shuffle: bool,
+    deterministic: bool,
+    max_pages_per_domain: Option<usize>,
+    max_requests: Option<usize>,
+    memory_cap: Option<usize>, // Bytes of page text kept, from --memory-cap-mb
+    eviction: Eviction,
+    pause_every: Option<usize>, // Crawled pages per reviewed batch
+    match_context: usize,       // Lines kept around each match when only matches are shown
+    max_render_lines: Option<usize>,
+    sessions: usize, // WebDriver sessions, and so workers, a crawl starts with
+    resume: Option<std::path::PathBuf>, // Saved session to browse instead of crawling
+    continue_last: bool,
+    baseline: Option<std::path::PathBuf>, // Saved session the crawl is compared against
+    shuffle_seed: Option<u64>,
+    adaptive_delay: bool,
+    sitemap_out: Option<std::path::PathBuf>,
+    compress_exports: bool,
+    clipboard: ClipboardMethod,
+    output_stream: Option<std::path::PathBuf>, // --output-fifo, or /dev/fd/<n> for --output-fd
+    output_fd: Option<u32>,                    // Kept so --output-fd is written back as given
+    metrics_port: Option<u16>,
+    max_delay: Duration, // Upper bound for --adaptive-delay
 }
 
-impl AppState {
-    fn new() -> Self {
-        AppState {
-            visited_urls: Vec::new(),
-            body_texts: HashMap::new(),
-            list_state: ListState::default(),
-            search_input: String::new(),
-            active_search_query: String::new(),
-            is_searching: false,
-            filtered_url_indices: Vec::new(),
-            content_scroll: 0,
-            content_area: Rect::default(), // Initialize with a default
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            proxy: None,
+            user_agent: None,
+            user_agent_file: None,
+            user_agents: Vec::new(),
+            set_terminal_title: false,
+            show_summary: true,
+            heartbeat: true,
+            webdriver_retries: 5,
+            webdriver_retry_interval: Duration::from_millis(500),
+            min_body_chars: 0,
+            extract_json_ld: false,
+            capture_html: false,
+            anchor_text: false,
+            link_attrs: Vec::new(),
+            follow_pagination: false,
+            spa_mode: false,
+            html_only: false,
+            pin_selection_top: false,
+            collapse_repeats: false,
+            dim_boilerplate: false,
+            seed_url: None,
+            seed_from_stdin: false,
+            print: false,
+            cat: false,
+            max_duration: None,
+            sort_query_params: false,
+            harvest_pattern: Regex::new(DEFAULT_HARVEST_PATTERN).expect("valid default pattern"),
+            keep_matching: None,
+            auth_skip_words: DEFAULT_AUTH_SKIP_WORDS
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+            no_ascend: false,
+            scope_domain: None,
+            subtree: false,
+            max_path_depth: None,
+            wait_for_selector: None,
+            content_selectors: Vec::new(),
+            fallback_selectors: Vec::new(),
+            strip_scripts: false,
+            on_empty_body: EmptyBodyAction::Placeholder,
+            list_format: DEFAULT_LIST_FORMAT.to_string(),
+            check_encoding: false,
+            wait_timeout: Duration::from_secs(10),
+            highlight_color: Color::Red,
+            scroll_indicator: ScrollIndicator::Title,
+            time_display: TimeDisplay::Relative,
+            url_display: UrlDisplay::Raw,
+            auto_select: AutoSelect::OnEmpty,
+            loop_threshold: None,
+            shuffle: false,
+            deterministic: false,
+            max_pages_per_domain: None,
+            max_requests: None,
+            memory_cap: None,
+            eviction: Eviction::Oldest,
+            pause_every: None,
+            match_context: 2,
+            max_render_lines: None,
+            sessions: 1,
+            resume: None,
+            continue_last: false,
+            baseline: None,
+            shuffle_seed: None,
+            adaptive_delay: false,
+            sitemap_out: None,
+            compress_exports: false,
+            clipboard: ClipboardMethod::Auto,
+            output_stream: None,
+            output_fd: None,
+            metrics_port: None,
+            max_delay: Duration::from_secs(5),
         }
     }
+}
 
-    // --- Methods for adding/updating/getting data (mostly unchanged) ---
-    fn add_crawl_result(&mut self, url: String, body: String) {
-        if !self.body_texts.contains_key(&url) {
-            let is_first_item = self.visited_urls.is_empty();
-            self.visited_urls.push(url.clone());
-            self.body_texts.insert(url, body);
-            self.update_filtered_list();
-            if is_first_item && !self.filtered_url_indices.is_empty() {
-                self.list_state.select(Some(0));
-                self.reset_or_find_scroll();
+impl Config {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut positional_seed = false; // A bare URL may replace --url from a profile
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--proxy" => config.proxy = Some(parse_proxy_url(&next_value(&mut args, &arg)?)?),
+                "--user-agent" => config.user_agent = Some(next_value(&mut args, &arg)?),
+                "--user-agent-file" => {
+                    config.user_agent_file = Some(next_value(&mut args, &arg)?.into())
+                }
+                "--set-terminal-title" => config.set_terminal_title = true,
+                "--no-summary" => config.show_summary = false,
+                "--no-heartbeat" => config.heartbeat = false,
+                "--jsonld" => config.extract_json_ld = true,
+                "--capture-html" => config.capture_html = true,
+                "--anchor-text" => config.anchor_text = true,
+                "--link-attr" => {
+                    let name = next_value(&mut args, &arg)?;
+                    if !is_attribute_name(&name) {
+                        return Err(format!(
+                            "'{}' expects an attribute name like data-href, got '{}'",
+                            arg, name
+                        ));
+                    }
+                    config.link_attrs.push(name);
+                }
+                "--follow-pagination" => config.follow_pagination = true,
+                "--spa-mode" => config.spa_mode = true,
+                "--html-only" => config.html_only = true,
+                "--pin-top" => config.pin_selection_top = true,
+                "--collapse-repeats" => config.collapse_repeats = true,
+                "--dim-boilerplate" => config.dim_boilerplate = true,
+                "--url" => config.seed_url = Some(parse_seed_url(&next_value(&mut args, &arg)?)?),
+                "--stdin" => config.seed_from_stdin = true,
+                "--print" => config.print = true,
+                "--cat" => config.cat = true,
+                "--resume" => config.resume = Some(next_value(&mut args, &arg)?.into()),
+                "--baseline" => config.baseline = Some(next_value(&mut args, &arg)?.into()),
+                "--continue-last" => config.continue_last = true,
+                "--sort-query-params" => config.sort_query_params = true,
+                "--shuffle" => config.shuffle = true,
+                "--deterministic" => config.deterministic = true,
+                "--adaptive-delay" => config.adaptive_delay = true,
+                "--sitemap-out" => config.sitemap_out = Some(next_value(&mut args, &arg)?.into()),
+                "--compress" => config.compress_exports = true,
+                "--clipboard" => {
+                    config.clipboard = ClipboardMethod::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--output-fifo" => {
+                    config.output_stream = Some(next_value(&mut args, &arg)?.into());
+                    config.output_fd = None;
+                }
+                "--output-fd" => {
+                    let fd: u32 = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    config.output_stream = Some(format!("/dev/fd/{}", fd).into());
+                    config.output_fd = Some(fd);
+                }
+                "--metrics-port" => {
+                    let port = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    if port == 0 {
+                        return Err("'--metrics-port' must be at least 1".to_string());
+                    }
+                    config.metrics_port = Some(port);
+                }
+                "--max-delay-ms" => {
+                    config.max_delay =
+                        Duration::from_millis(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--seed" => {
+                    config.shuffle_seed = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--no-ascend" => config.no_ascend = true,
+                "--max-pages-per-domain" => {
+                    config.max_pages_per_domain =
+                        Some(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--max-requests" => {
+                    config.max_requests = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--memory-cap-mb" => {
+                    let megabytes: usize = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    if megabytes == 0 {
+                        return Err("'--memory-cap-mb' must be at least 1".to_string());
+                    }
+                    config.memory_cap = Some(megabytes.saturating_mul(1024 * 1024));
+                }
+                "--eviction" => {
+                    config.eviction = Eviction::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--pause-every" => {
+                    let pages = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    if pages == 0 {
+                        return Err("'--pause-every' must be at least 1".to_string());
+                    }
+                    config.pause_every = Some(pages);
+                }
+                "--subtree" => config.subtree = true,
+                "--scope-domain" => {
+                    config.scope_domain = Some(parse_scope_domain(&next_value(&mut args, &arg)?)?)
+                }
+                "--max-path-depth" => {
+                    config.max_path_depth = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--sessions" => {
+                    config.sessions = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    if config.sessions == 0 {
+                        return Err("'--sessions' must be at least 1".to_string());
+                    }
+                }
+                "--match-context" => {
+                    config.match_context = parse_number(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--max-render-lines" => {
+                    let lines = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    if lines == 0 {
+                        return Err("'--max-render-lines' must be at least 1".to_string());
+                    }
+                    config.max_render_lines = Some(lines);
+                }
+                "--loop-threshold" => {
+                    config.loop_threshold = Some(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--check-encoding" => config.check_encoding = true,
+                "--list-format" => config.list_format = next_value(&mut args, &arg)?,
+                "--content-selector" => config.content_selectors.push(next_value(&mut args, &arg)?),
+                "--fallback-selector" => {
+                    config.fallback_selectors.push(next_value(&mut args, &arg)?)
+                }
+                "--strip-scripts" => config.strip_scripts = true,
+                "--on-empty-body" => {
+                    config.on_empty_body =
+                        EmptyBodyAction::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--wait-for-selector" => {
+                    config.wait_for_selector = Some(next_value(&mut args, &arg)?)
+                }
+                "--wait-timeout-secs" => {
+                    config.wait_timeout =
+                        Duration::from_secs(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                "--no-auth-skip" => config.auth_skip_words.clear(),
+                "--auth-skip-words" => {
+                    config.auth_skip_words = next_value(&mut args, &arg)?
+                        .split(',')
+                        .map(|word| word.trim().to_lowercase())
+                        .filter(|word| !word.is_empty())
+                        .collect()
+                }
+                "--harvest" => {
+                    let pattern = next_value(&mut args, &arg)?;
+                    config.harvest_pattern = Regex::new(&pattern)
+                        .map_err(|e| format!("invalid value for '{}': {}", arg, e))?
+                }
+                "--keep-matching" => {
+                    let pattern = next_value(&mut args, &arg)?;
+                    config.keep_matching = Some(
+                        Regex::new(&pattern)
+                            .map_err(|e| format!("invalid value for '{}': {}", arg, e))?,
+                    )
+                }
+                "--max-duration-secs" => {
+                    let secs = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    config.max_duration = Some(Duration::from_secs(secs))
+                }
+                "--highlight-color" => {
+                    config.highlight_color = parse_color(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--scroll-indicator" => {
+                    config.scroll_indicator =
+                        ScrollIndicator::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--display-url" => {
+                    config.url_display = UrlDisplay::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--auto-select" => {
+                    config.auto_select = AutoSelect::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--time-display" => {
+                    config.time_display = TimeDisplay::parse(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--min-body-chars" => {
+                    config.min_body_chars = parse_number(&next_value(&mut args, &arg)?, &arg)?
+                }
+                "--webdriver-retries" => {
+                    config.webdriver_retries = parse_number(&next_value(&mut args, &arg)?, &arg)?;
+                    if config.webdriver_retries == 0 {
+                        return Err("'--webdriver-retries' must be at least 1".to_string());
+                    }
+                }
+                "--webdriver-retry-ms" => {
+                    config.webdriver_retry_interval =
+                        Duration::from_millis(parse_number(&next_value(&mut args, &arg)?, &arg)?)
+                }
+                seed if !seed.starts_with('-') && !positional_seed => {
+                    config.seed_url = Some(parse_seed_url(seed)?);
+                    positional_seed = true;
+                }
+                other => return Err(format!("unknown argument '{}'", other)),
             }
         }
+        if let Some(path) = &config.user_agent_file {
+            config.user_agents = read_user_agents(path)?;
+        }
+        if config.print && config.cat {
+            return Err("'--print' can't be combined with '--cat'".to_string());
+        }
+        if config.pause_every.is_some() && (config.print || config.cat) {
+            return Err("'--pause-every' needs the TUI to resume the crawl".to_string());
+        }
+        if config.deterministic && config.shuffle {
+            return Err("'--deterministic' can't be combined with '--shuffle'".to_string());
+        }
+        if config.deterministic && config.sessions > 1 {
+            return Err(
+                "'--deterministic' crawls with a single worker, drop '--sessions'".to_string(),
+            );
+        }
+        if config.seed_from_stdin && config.seed_url.is_some() {
+            return Err("'--stdin' can't be combined with a seed URL".to_string());
+        }
+        Ok(config)
     }
 
-    fn update_filtered_list(&mut self) {
-        let query = self.active_search_query.to_lowercase();
-        let previously_selected_original_index = self.get_selected_original_index();
-
-        self.filtered_url_indices = self
-            .visited_urls
-            .iter()
-            .enumerate()
-            .filter(|(_idx, url)| {
-                if query.is_empty() {
-                    true
-                } else {
-                    self.body_texts
-                        .get(*url)
-                        .map_or(false, |body| body.to_lowercase().contains(&query))
-                }
-            })
-            .map(|(idx, _url)| idx)
-            .collect();
+    // The options that differ from the defaults, as arguments that rebuild this config.
+    // Run modes (--print, --cat, --resume, --continue-last) aren't part of a crawl's setup.
+    fn to_cli_args(&self) -> Vec<String> {
+        let defaults = Config::default();
+        let mut args: Vec<String> = Vec::new();
+        let mut push = |flag: &str, value: Option<String>| {
+            args.push(flag.to_string());
+            args.extend(value);
+        };
 
-        if let Some(original_idx) = previously_selected_original_index {
-            if let Some(new_filtered_pos) = self
-                .filtered_url_indices
-                .iter()
-                .position(|&idx| idx == original_idx)
-            {
-                self.list_state.select(Some(new_filtered_pos));
-            } else {
-                self.select_first_or_last();
-            }
-        } else {
-            self.select_first_or_last();
+        if let Some(url) = &self.seed_url {
+            push("--url", Some(url.to_string()));
+        }
+        if let Some(proxy) = &self.proxy {
+            push("--proxy", Some(proxy.to_string()));
+        }
+        if let Some(user_agent) = &self.user_agent {
+            push("--user-agent", Some(user_agent.clone()));
+        }
+        if let Some(path) = &self.user_agent_file {
+            push(
+                "--user-agent-file",
+                Some(path.to_string_lossy().into_owned()),
+            );
+        }
+        if self.set_terminal_title {
+            push("--set-terminal-title", None);
+        }
+        if !self.show_summary {
+            push("--no-summary", None);
+        }
+        if !self.heartbeat {
+            push("--no-heartbeat", None);
+        }
+        if self.webdriver_retries != defaults.webdriver_retries {
+            push(
+                "--webdriver-retries",
+                Some(self.webdriver_retries.to_string()),
+            );
+        }
+        if self.webdriver_retry_interval != defaults.webdriver_retry_interval {
+            let millis = self.webdriver_retry_interval.as_millis();
+            push("--webdriver-retry-ms", Some(millis.to_string()));
+        }
+        if self.min_body_chars != 0 {
+            push("--min-body-chars", Some(self.min_body_chars.to_string()));
+        }
+        if self.capture_html {
+            push("--capture-html", None);
+        }
+        if self.extract_json_ld {
+            push("--jsonld", None);
+        }
+        for name in &self.link_attrs {
+            push("--link-attr", Some(name.clone()));
+        }
+        if self.anchor_text {
+            push("--anchor-text", None);
+        }
+        if self.spa_mode {
+            push("--spa-mode", None);
+        }
+        if self.follow_pagination {
+            push("--follow-pagination", None);
+        }
+        if self.html_only {
+            push("--html-only", None);
+        }
+        if self.pin_selection_top {
+            push("--pin-top", None);
         }
+        if self.collapse_repeats {
+            push("--collapse-repeats", None);
+        }
+        if self.dim_boilerplate {
+            push("--dim-boilerplate", None);
+        }
+        if let Some(limit) = self.max_duration {
+            push("--max-duration-secs", Some(limit.as_secs().to_string()));
+        }
+        if self.sort_query_params {
+            push("--sort-query-params", None);
+        }
+        if let Some(pattern) = &self.keep_matching {
+            push("--keep-matching", Some(pattern.as_str().to_string()));
+        }
+        if self.harvest_pattern.as_str() != DEFAULT_HARVEST_PATTERN {
+            push("--harvest", Some(self.harvest_pattern.as_str().to_string()));
+        }
+        if self.auth_skip_words.is_empty() {
+            push("--no-auth-skip", None);
+        } else if self.auth_skip_words != defaults.auth_skip_words {
+            push("--auth-skip-words", Some(self.auth_skip_words.join(",")));
+        }
+        if self.no_ascend {
+            push("--no-ascend", None);
+        }
+        if self.subtree {
+            push("--subtree", None);
+        }
+        if let Some(domain) = &self.scope_domain {
+            push("--scope-domain", Some(domain.clone()));
+        }
+        if let Some(depth) = self.max_path_depth {
+            push("--max-path-depth", Some(depth.to_string()));
+        }
+        if let Some(selector) = &self.wait_for_selector {
+            push("--wait-for-selector", Some(selector.clone()));
+        }
+        if self.wait_timeout != defaults.wait_timeout {
+            push(
+                "--wait-timeout-secs",
+                Some(self.wait_timeout.as_secs().to_string()),
+            );
+        }
+        for selector in &self.content_selectors {
+            push("--content-selector", Some(selector.clone()));
+        }
+        for selector in &self.fallback_selectors {
+            push("--fallback-selector", Some(selector.clone()));
+        }
+        if self.strip_scripts {
+            push("--strip-scripts", None);
+        }
+        if self.on_empty_body != defaults.on_empty_body {
+            push(
+                "--on-empty-body",
+                Some(self.on_empty_body.name().to_string()),
+            );
+        }
+        if self.list_format != defaults.list_format {
+            push("--list-format", Some(self.list_format.clone()));
+        }
+        if self.check_encoding {
+            push("--check-encoding", None);
+        }
+        if self.scroll_indicator != defaults.scroll_indicator {
+            push(
+                "--scroll-indicator",
+                Some(self.scroll_indicator.name().to_string()),
+            );
+        }
+        if self.url_display != defaults.url_display {
+            push("--display-url", Some(self.url_display.name().to_string()));
+        }
+        if self.auto_select != defaults.auto_select {
+            push("--auto-select", Some(self.auto_select.name().to_string()));
+        }
+        if self.time_display != defaults.time_display {
+            push("--time-display", Some(self.time_display.name().to_string()));
+        }
+        if self.highlight_color != defaults.highlight_color {
+            push("--highlight-color", Some(color_name(self.highlight_color)));
+        }
+        if let Some(threshold) = self.loop_threshold {
+            push("--loop-threshold", Some(threshold.to_string()));
+        }
+        if self.deterministic {
+            push("--deterministic", None);
+        }
+        if self.shuffle {
+            push("--shuffle", None);
+        }
+        if let Some(seed) = self.shuffle_seed {
+            push("--seed", Some(seed.to_string()));
+        }
+        if let Some(limit) = self.max_pages_per_domain {
+            push("--max-pages-per-domain", Some(limit.to_string()));
+        }
+        if let Some(budget) = self.max_requests {
+            push("--max-requests", Some(budget.to_string()));
+        }
+        if let Some(cap) = self.memory_cap {
+            push("--memory-cap-mb", Some((cap / (1024 * 1024)).to_string()));
+        }
+        if self.eviction != defaults.eviction {
+            push("--eviction", Some(self.eviction.name().to_string()));
+        }
+        if let Some(pages) = self.pause_every {
+            push("--pause-every", Some(pages.to_string()));
+        }
+        if self.sessions != defaults.sessions {
+            push("--sessions", Some(self.sessions.to_string()));
+        }
+        if self.match_context != defaults.match_context {
+            push("--match-context", Some(self.match_context.to_string()));
+        }
+        if let Some(lines) = self.max_render_lines {
+            push("--max-render-lines", Some(lines.to_string()));
+        }
+        if self.adaptive_delay {
+            push("--adaptive-delay", None);
+        }
+        if let Some(path) = &self.baseline {
+            push("--baseline", Some(path.to_string_lossy().into_owned()));
+        }
+        if let Some(path) = &self.sitemap_out {
+            push("--sitemap-out", Some(path.to_string_lossy().into_owned()));
+        }
+        if self.compress_exports {
+            push("--compress", None);
+        }
+        if self.clipboard != defaults.clipboard {
+            push("--clipboard", Some(self.clipboard.name().to_string()));
+        }
+        if let Some(fd) = self.output_fd {
+            push("--output-fd", Some(fd.to_string()));
+        } else if let Some(path) = &self.output_stream {
+            push("--output-fifo", Some(path.to_string_lossy().into_owned()));
+        }
+        if let Some(port) = self.metrics_port {
+            push("--metrics-port", Some(port.to_string()));
+        }
+        if self.max_delay != defaults.max_delay {
+            push(
+                "--max-delay-ms",
+                Some(self.max_delay.as_millis().to_string()),
+            );
+        }
+        args
     }
 
-    fn select_first_or_last(&mut self) {
-        if !self.filtered_url_indices.is_empty() {
-            self.list_state.select(Some(0));
-        } else {
-            self.list_state.select(None);
+    // WebDriver capabilities requested for every browser session
+    fn capabilities(&self, user_agent: Option<&str>) -> serde_json::Map<String, serde_json::Value> {
+        let mut caps = serde_json::Map::new();
+        if let Some(proxy) = &self.proxy {
+            caps.insert("proxy".to_string(), proxy_capability(proxy));
+        }
+        if let Some(user_agent) = user_agent {
+            caps.extend(user_agent_capabilities(user_agent));
         }
+        caps
     }
 
-    fn get_selected_original_index(&self) -> Option<usize> {
-        self.list_state
-            .selected()
-            .and_then(|selected_filtered_idx| self.filtered_url_indices.get(selected_filtered_idx))
-            .copied()
+    // The User-Agent of the nth WebDriver session a crawl opens
+    fn user_agent_for(&self, session: usize) -> Option<&str> {
+        if let Some(user_agent) = &self.user_agent {
+            return Some(user_agent);
+        }
+        let count = self.user_agents.len();
+        (count > 0).then(|| self.user_agents[session % count].as_str())
     }
+}
 
-    fn get_displayed_urls(&self) -> Vec<&String> {
-        self.filtered_url_indices
-            .iter()
-            .map(|&idx| &self.visited_urls[idx])
-            .collect()
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("'{}' requires a value", flag))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, flag: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' expects a number, got '{}'", flag, value))
+}
+
+// Where the content panel shows how far it's scrolled
+#[derive(Clone, Copy, PartialEq)]
+enum ScrollIndicator {
+    Title,  // "Content (Scroll: N): url"
+    Corner, // "row/rows" on the bottom border, leaving the title to the URL
+    Hidden,
+}
+
+impl ScrollIndicator {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "title" => Ok(ScrollIndicator::Title),
+            "corner" => Ok(ScrollIndicator::Corner),
+            "hidden" => Ok(ScrollIndicator::Hidden),
+            _ => Err(format!(
+                "'{}' expects title, corner or hidden, got '{}'",
+                flag, value
+            )),
+        }
     }
 
-    fn get_selected_content(&self) -> Option<&String> {
-        self.get_selected_original_index()
-            .and_then(|original_idx| self.visited_urls.get(original_idx))
-            .and_then(|url| self.body_texts.get(url))
+    fn name(self) -> &'static str {
+        match self {
+            ScrollIndicator::Title => "title",
+            ScrollIndicator::Corner => "corner",
+            ScrollIndicator::Hidden => "hidden",
+        }
     }
+}
 
-    fn get_selected_url_str(&self) -> Option<&str> {
-        self.get_selected_original_index()
-            .map(|original_idx| self.visited_urls[original_idx].as_str())
+// When a page arriving in the list takes the selection
+#[derive(Clone, Copy, PartialEq)]
+enum AutoSelect {
+    Always,  // The first page of every crawl, even into a list that already has pages
+    OnEmpty, // Only the first page of an empty list
+    Never,
+}
+
+impl AutoSelect {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "always" => Ok(AutoSelect::Always),
+            "on-empty" => Ok(AutoSelect::OnEmpty),
+            "never" => Ok(AutoSelect::Never),
+            _ => Err(format!(
+                "'{}' expects always, on-empty or never, got '{}'",
+                flag, value
+            )),
+        }
     }
 
-    fn find_first_match_line(&self) -> Option<u16> {
-        if self.active_search_query.is_empty() {
-            return None;
+    fn name(self) -> &'static str {
+        match self {
+            AutoSelect::Always => "always",
+            AutoSelect::OnEmpty => "on-empty",
+            AutoSelect::Never => "never",
         }
-        let query_lower = self.active_search_query.to_lowercase();
-        self.get_selected_content().and_then(|content| {
-            content
-                .lines()
-                .position(|line| line.to_lowercase().contains(&query_lower))
-                .map(|line_idx| line_idx as u16)
-        })
     }
+}
 
-    fn reset_or_find_scroll(&mut self) {
-        if !self.active_search_query.is_empty() {
-            self.content_scroll = self.find_first_match_line().unwrap_or(0);
-        } else {
-            self.content_scroll = 0;
+// How URLs are written in the list, popups and exports; pages are always fetched and
+// keyed by the URL as discovered
+#[derive(Clone, Copy, PartialEq)]
+enum UrlDisplay {
+    Raw,
+    Normalized, // Without a trailing slash, except on the root path
+}
+
+impl UrlDisplay {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "raw" => Ok(UrlDisplay::Raw),
+            "normalized" => Ok(UrlDisplay::Normalized),
+            _ => Err(format!(
+                "'{}' expects raw or normalized, got '{}'",
+                flag, value
+            )),
         }
     }
 
-    // --- Methods for UI State Manipulation (mostly unchanged) ---
-    fn select_next(&mut self) {
-        let len = self.filtered_url_indices.len();
-        if len == 0 {
-            return;
+    fn name(self) -> &'static str {
+        match self {
+            UrlDisplay::Raw => "raw",
+            UrlDisplay::Normalized => "normalized",
         }
-        let i = match self.list_state.selected() {
-            Some(i) => (i + 1) % len,
-            None => 0,
-        };
-        self.list_state.select(Some(i));
-        self.reset_or_find_scroll();
     }
 
-    fn select_previous(&mut self) {
-        let len = self.filtered_url_indices.len();
-        if len == 0 {
-            return;
+    fn apply(self, url: &str) -> Cow<'_, str> {
+        match self {
+            UrlDisplay::Raw => Cow::Borrowed(url),
+            UrlDisplay::Normalized => normalize_display_url(url),
         }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    len - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.list_state.select(Some(i));
-        self.reset_or_find_scroll();
     }
+}
 
-    // Not used. Could be useful in future scenarios where we want to select the last item in the list.
-    // fn select_last(&mut self) {
-    //     let len = self.filtered_url_indices.len();
-    //     if len > 0 {
-    //         self.list_state.select(Some(len - 1));
-    //     } else {
-    //         self.list_state.select(None);
-    //     }
-    //     self.reset_or_find_scroll(); // Ensure scroll updates even when selecting last
-    // }
+// How crawl times are shown: "2m ago" suits a live crawl, full timestamps an old session
+#[derive(Clone, Copy, PartialEq)]
+enum TimeDisplay {
+    Relative,
+    Absolute, // ISO 8601, in UTC
+}
 
-    fn start_search(&mut self) {
-        self.is_searching = true;
-        self.search_input = self.active_search_query.clone();
+impl TimeDisplay {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "relative" => Ok(TimeDisplay::Relative),
+            "absolute" => Ok(TimeDisplay::Absolute),
+            _ => Err(format!(
+                "'{}' expects relative or absolute, got '{}'",
+                flag, value
+            )),
+        }
     }
 
-    fn finalize_search(&mut self) {
-        self.is_searching = false;
-        self.active_search_query = self.search_input.clone();
+    fn name(self) -> &'static str {
+        match self {
+            TimeDisplay::Relative => "relative",
+            TimeDisplay::Absolute => "absolute",
+        }
+    }
+}
+
+// What the crawler stores for a page that has no <body> element
+#[derive(Clone, Copy, PartialEq)]
+enum EmptyBodyAction {
+    Placeholder, // BODY_NOT_FOUND, so the page shows up as an error
+    Skip,        // Reported as a failure, nothing stored
+    Retry,       // Look once more after EMPTY_BODY_RETRY_DELAY, then the placeholder
+    Source,      // The raw page source
+}
+
+impl EmptyBodyAction {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "placeholder" => Ok(EmptyBodyAction::Placeholder),
+            "skip" => Ok(EmptyBodyAction::Skip),
+            "retry" => Ok(EmptyBodyAction::Retry),
+            "source" => Ok(EmptyBodyAction::Source),
+            _ => Err(format!(
+                "'{}' expects placeholder, skip, retry or source, got '{}'",
+                flag, value
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            EmptyBodyAction::Placeholder => "placeholder",
+            EmptyBodyAction::Skip => "skip",
+            EmptyBodyAction::Retry => "retry",
+            EmptyBodyAction::Source => "source",
+        }
+    }
+}
+
+// Which stored page --memory-cap-mb drops first
+#[derive(Clone, Copy, PartialEq)]
+enum Eviction {
+    Oldest,  // Crawled longest ago; a re-crawl makes a page new again
+    Largest, // Most text
+    Fifo,    // First into the list, whenever it was last crawled
+}
+
+impl Eviction {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "oldest" => Ok(Eviction::Oldest),
+            "largest" => Ok(Eviction::Largest),
+            "fifo" => Ok(Eviction::Fifo),
+            _ => Err(format!(
+                "'{}' expects oldest, largest or fifo, got '{}'",
+                flag, value
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Eviction::Oldest => "oldest",
+            Eviction::Largest => "largest",
+            Eviction::Fifo => "fifo",
+        }
+    }
+}
+
+// Terminal color names (dark-gray or darkgray, light-red, ...) or a #rrggbb hex value
+fn parse_color(value: &str, flag: &str) -> Result<Color, String> {
+    let name = value.to_lowercase().replace(['-', '_'], "");
+    let color = match name.as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        hex if hex.len() == 7 && hex.is_ascii() && hex.starts_with('#') => {
+            let channel = |range| u8::from_str_radix(&hex[range], 16);
+            match (channel(1..3), channel(3..5), channel(5..7)) {
+                (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+                _ => return Err(format!("'{}' expects a color, got '{}'", flag, value)),
+            }
+        }
+        _ => return Err(format!("'{}' expects a color, got '{}'", flag, value)),
+    };
+    Ok(color)
+}
+
+// Inverse of parse_color
+fn color_name(color: Color) -> String {
+    match color {
+        Color::Black => "black".to_string(),
+        Color::Red => "red".to_string(),
+        Color::Green => "green".to_string(),
+        Color::Yellow => "yellow".to_string(),
+        Color::Blue => "blue".to_string(),
+        Color::Magenta => "magenta".to_string(),
+        Color::Cyan => "cyan".to_string(),
+        Color::Gray => "gray".to_string(),
+        Color::DarkGray => "dark-gray".to_string(),
+        Color::LightRed => "light-red".to_string(),
+        Color::LightGreen => "light-green".to_string(),
+        Color::LightYellow => "light-yellow".to_string(),
+        Color::LightBlue => "light-blue".to_string(),
+        Color::LightMagenta => "light-magenta".to_string(),
+        Color::LightCyan => "light-cyan".to_string(),
+        Color::White => "white".to_string(),
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        // Not produced by parse_color
+        Color::Reset | Color::Indexed(_) => "red".to_string(),
+    }
+}
+
+// Single-quotes an argument for a POSIX shell when it needs it
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// Crawl seeds must be plain web pages
+fn parse_seed_url(value: &str) -> Result<Url, String> {
+    match Url::parse(value) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(url),
+        Ok(url) => Err(format!(
+            "unsupported URL scheme '{}' (expected http or https)",
+            url.scheme()
+        )),
+        Err(e) => Err(format!("invalid URL '{}': {}", value, e)),
+    }
+}
+
+// First line of stdin, for --stdin
+fn read_stdin_seed() -> Result<Url, String> {
+    let mut line = String::new();
+    io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("can't read the seed URL from stdin: {}", e))?;
+    match line.trim() {
+        "" => Err("'--stdin' got no URL".to_string()),
+        seed => parse_seed_url(seed),
+    }
+}
+
+// One User-Agent per line; blank lines and # comments are skipped
+fn read_user_agents(path: &std::path::Path) -> Result<Vec<String>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("can't read user agents from {}: {}", path.display(), e))?;
+    let user_agents: Vec<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if user_agents.is_empty() {
+        return Err(format!(
+            "'--user-agent-file' found no user agents in {}",
+            path.display()
+        ));
+    }
+    Ok(user_agents)
+}
+
+// A bare host name, no scheme, port or path. "*.site.com" and ".site.com" both mean
+// site.com and its subdomains, stored as ".site.com".
+fn parse_scope_domain(value: &str) -> Result<String, String> {
+    let (subdomains, host) = match value.strip_prefix("*.").or_else(|| value.strip_prefix('.')) {
+        Some(host) => (true, host),
+        None => (false, value),
+    };
+    let invalid = || {
+        format!(
+            "'--scope-domain' expects a domain like site.com, got '{}'",
+            value
+        )
+    };
+    if host.is_empty() || host.contains(|c: char| "/:?#@*".contains(c) || c.is_whitespace()) {
+        return Err(invalid());
+    }
+    // Url also lowercases the name and turns an internationalized one into its ASCII form
+    let domain = Url::parse(&format!("http://{}/", host))
+        .ok()
+        .and_then(|url| url.domain().map(str::to_string))
+        .ok_or_else(invalid)?;
+    Ok(if subdomains {
+        format!(".{}", domain)
+    } else {
+        domain
+    })
+}
+
+fn parse_proxy_url(value: &str) -> Result<Url, String> {
+    let url = Url::parse(value).map_err(|e| format!("invalid proxy URL '{}': {}", value, e))?;
+    if !matches!(url.scheme(), "http" | "https" | "socks4" | "socks5") {
+        return Err(format!(
+            "unsupported proxy scheme '{}' (expected http, https, socks4 or socks5)",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none() || url.port_or_known_default().is_none() {
+        return Err(format!("proxy URL '{}' needs a host and port", value));
+    }
+    if url.path() != "/" && !url.path().is_empty() {
+        return Err(format!("proxy URL '{}' must not contain a path", value));
+    }
+    Ok(url)
+}
+
+// W3C WebDriver manual proxy configuration for the given proxy URL
+fn proxy_capability(proxy: &Url) -> serde_json::Value {
+    let host_port = format!(
+        "{}:{}",
+        proxy.host_str().unwrap_or_default(),
+        proxy.port_or_known_default().unwrap_or_default()
+    );
+    match proxy.scheme() {
+        "socks4" | "socks5" => serde_json::json!({
+            "proxyType": "manual",
+            "socksProxy": host_port,
+            "socksVersion": if proxy.scheme() == "socks4" { 4 } else { 5 },
+        }),
+        _ => serde_json::json!({
+            "proxyType": "manual",
+            "httpProxy": host_port,
+            "sslProxy": host_port,
+        }),
+    }
+}
+
+// There's no standard capability for the User-Agent, so it's set for Chrome and Firefox
+// both; each driver ignores the other's vendor options
+fn user_agent_capabilities(user_agent: &str) -> serde_json::Map<String, serde_json::Value> {
+    let mut caps = serde_json::Map::new();
+    caps.insert(
+        "goog:chromeOptions".to_string(),
+        serde_json::json!({ "args": [format!("--user-agent={}", user_agent)] }),
+    );
+    caps.insert(
+        "moz:firefoxOptions".to_string(),
+        serde_json::json!({ "prefs": { "general.useragent.override": user_agent } }),
+    );
+    caps
+}
+
+// --- Text Input ---
+
+// Single-line editable text with a cursor, shared by the URL prompt and the search box
+#[derive(Default)]
+struct TextInput {
+    text: String,
+    cursor: usize, // In chars, 0..=len
+}
+
+impl TextInput {
+    fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    // Replaces the contents and puts the cursor at the end
+    fn set(&mut self, text: String) {
+        self.cursor = text.chars().count();
+        self.text = text;
+    }
+
+    fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    // Byte offset of a char position, for editing the String in place
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    // Applies an editing key; false if the key isn't one the input handles
+    fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let len = self.text.chars().count();
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let at = self.byte_index(self.cursor);
+                self.text.insert(at, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let at = self.byte_index(self.cursor);
+                self.text.remove(at);
+            }
+            KeyCode::Delete if self.cursor < len => {
+                let at = self.byte_index(self.cursor);
+                self.text.remove(at);
+            }
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => return false,
+        }
+        true
+    }
+
+    // Terminal columns between the start of the text and the cursor
+    fn cursor_column(&self) -> u16 {
+        self.text[..self.byte_index(self.cursor)]
+            .width()
+            .min(u16::MAX as usize) as u16
+    }
+}
+
+// Places the terminal cursor inside an input drawn at `x` on row `y`, if it fits in `area`
+fn place_input_cursor<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    x: u16,
+    y: u16,
+    input: &TextInput,
+) {
+    let column = x.saturating_add(input.cursor_column());
+    if column < area.x + area.width.saturating_sub(1) {
+        f.set_cursor(column, y);
+    }
+}
+
+// --- Application State ---
+
+struct AppState {
+    launch_config: Config, // What this crawl was started with, for copying as a command
+    seed_url: String,      // The crawl's starting point, as it's stored in the list
+    visited_urls: Vec<String>,
+    body_texts: HashMap<String, String>,
+    list_state: ListState,
+    search_input: TextInput,
+    active_search_query: String,
+    search_history: Vec<String>, // Applied queries, most recent first
+    history_state: ListState,    // Selection inside the recent queries popup
+    is_searching: bool,
+    line_input: Option<TextInput>, // The "go to line" prompt while it's open
+    profile_input: Option<TextInput>, // Name for the profile being saved (K)
+    search_scope: SearchScope,
+    whole_word: bool, // Only match the query between word boundaries
+    term_mode: TermMode,
+    filtered_url_indices: Vec<usize>,
+    content_scroll: u16,
+    content_area: Rect, // Store the area/bounds of the content panel
+    crawl_control: Arc<CrawlControl>,
+    sessions: Arc<SessionPool>, // WebDriver sessions kept between workers and refreshes
+    popup: Option<Popup>,       // Overlay drawn above the main view, dismissed by a key press
+    total_bytes: usize,         // Cumulative size of all extracted body texts
+    follow_bottom: bool,        // Keep the content scrolled to the end as the selected page updates
+    pin_selection_top: bool,    // Re-select the first list item whenever a page arrives
+    focus: Focus,
+    content_cursor: u16, // Line of the selected content the cursor is on (content focus)
+    current_match: Option<usize>, // Which of match_positions n/N last went to
+    status_message: Option<String>, // Feedback for the last action, cleared on the next key
+    errors: HashMap<usize, CrawlError>, // Why each failed page failed, by visited_urls index
+    crawl_started_at: Instant,
+    crawl_finished_at: Option<Instant>,
+    crawler_status: Option<String>, // Latest progress note from the crawler task
+    heartbeat: bool,
+    poll_ticks: usize, // Passes of the main loop, which the heartbeat dot blinks with
+    last_crawler_message: Instant,
+    max_duration: Option<Duration>, // Wall-clock budget for the crawl, shown as a countdown
+    min_body_chars: usize,          // Pages with shorter bodies are left out of the list
+    page_links: HashMap<String, Vec<String>>, // Outbound links of each crawled page
+    links_state: ListState,         // Selection inside the links popup
+    url_queue: UrlQueue,            // Shared with the crawler for UI-driven enqueueing
+    visited: VisitedSet,            // Shared with the crawler so a refresh can re-crawl pages
+    refresh_pending: HashSet<String>, // Pages queued by a refresh that haven't come back yet
+    refresh_total: usize,
+    restart_crawler: bool, // Set when queued work needs a crawler and the last one exited
+    worker_request: Option<WorkerRequest>, // Picked up by run_app, which owns the workers
+    worker_count: usize,
+    show_url_list: bool,
+    fullscreen_content: bool,      // Only the content panel, for reading
+    domain_filter: Option<String>, // List only pages on this host (h)
+    pinned: Vec<usize>, // visited_urls indices listed first whatever the filters, in pin order
+    json_ld: HashMap<String, Vec<serde_json::Value>>, // Structured data found on each page
+    html_sources: HashMap<String, String>, // Each page's HTML, with --capture-html
+    anchors: HashMap<String, Vec<(String, String)>>, // (link, anchor text) per page, with --anchor-text
+    popup_scroll: u16,                               // Scroll offset of scrollable popups
+    depths: HashMap<String, usize>,                  // Link hops from the seed per crawled URL
+    show_depth_chart: bool,
+    show_search_bar: bool,
+    show_status_bar: bool,
+    harvest_pattern: Regex,
+    harvested: Vec<String>, // Unique harvest matches, computed when the popup opens
+    newest_first: bool,     // List the most recently crawled pages at the top
+    slowest_first: bool,    // Order the list by load time instead of crawl order
+    view_mode: ViewMode,
+    tree_depths: Vec<usize>, // Tree view: nesting of each filtered_url_indices entry
+    group_sizes: Vec<usize>, // Unique view: pages sharing each row's body, 0 for expanded members
+    expanded_groups: HashSet<u64>, // Body hashes of the groups expanded in the unique view
+    highlight_color: Color,
+    scroll_indicator: ScrollIndicator,
+    time_display: TimeDisplay,
+    url_display: UrlDisplay,
+    auto_select: AutoSelect,
+    awaiting_first_result: bool, // No page of the current crawl has been listed yet
+    selection_anchor: Option<u16>, // Content line where a visual-line selection started
+    wrap_lines: bool,            // Word-wrap the content; content_scroll then counts rows
+    show_domains: bool,          // Side panel with the pages crawled per host
+    clean_view: bool,            // Dedent and expand tabs when displaying content
+    show_only_matches: bool,     // Collapse the content to search matches plus context
+    collapse_repeats: bool,      // Draw runs of identical lines once with a (×N) count
+    dim_boilerplate: bool,       // Draw lines shared by many pages dimmed
+    line_frequency: HashMap<u64, usize>, // Pages each distinct trimmed line is on, by hash
+    counted_pages: usize,        // Pages whose lines are in line_frequency
+    full_url_line: bool,         // Spell out a URL too long for the content title above it
+    match_context: usize,
+    max_render_lines: Option<usize>,
+    titles: HashMap<String, String>, // <title> of each crawled page
+    final_urls: HashMap<String, String>, // Requested URL -> where it redirected to
+    content_sources: HashMap<String, String>, // Selectors each page's text came from
+    parents: HashMap<String, String>, // Page -> the page it was found on; a spanning tree
+    show_final_urls: bool,           // Show redirect targets instead of the requested URLs
+    crawled_at: HashMap<String, u64>, // When each page was loaded, in Unix seconds
+    load_times: HashMap<String, u64>, // Milliseconds each page took to load and extract
+    sitemap_out: Option<std::path::PathBuf>,
+    compress_exports: bool,
+    clipboard: ClipboardMethod,
+    list_format: String,
+    check_encoding: bool,
+    encoding_issues: HashMap<String, usize>, // U+FFFD count of pages that look mis-decoded
+    discarded_pages: usize,                  // Crawled but not kept, per --keep-matching
+    memory_cap: Option<usize>,
+    eviction: Eviction,
+    evicted_urls: Vec<String>, // Dropped to stay under --memory-cap-mb, in eviction order
+    evicted_bytes: usize,      // Their share of total_bytes
+    queue_snapshot: Vec<String>, // Pending URLs as of the last refresh, for the queue popup
+    queue_state: ListState,
+    baseline: Option<Baseline>, // --baseline: an earlier crawl to compare this one against
+    baseline_diff: Vec<(BaselineChange, String)>, // As of the last comparison
+    baseline_state: ListState,
+}
+
+// What the search query is matched against
+#[derive(Clone, Copy, PartialEq)]
+enum SearchScope {
+    Body,
+    Title,
+    Url,
+}
+
+impl SearchScope {
+    fn label(self) -> &'static str {
+        match self {
+            SearchScope::Body => "bodies",
+            SearchScope::Title => "titles",
+            SearchScope::Url => "URLs",
+        }
+    }
+}
+
+// How a query with several words is matched
+#[derive(Clone, Copy, PartialEq)]
+enum TermMode {
+    Phrase, // The whole query as one piece of text
+    Any,    // Pages with any of its words
+    All,    // Pages with every one of its words, anywhere on the page
+}
+
+impl TermMode {
+    fn label(self) -> &'static str {
+        match self {
+            TermMode::Phrase => "",
+            TermMode::Any => " (any word)",
+            TermMode::All => " (all words)",
+        }
+    }
+}
+
+// How the URL list is laid out
+#[derive(Clone, Copy, PartialEq)]
+enum ViewMode {
+    Flat,   // One row per page in list order
+    Tree,   // Each page indented under the page that linked to it
+    Unique, // One row per distinct body, with the pages sharing it folded underneath
+}
+
+// Which panel the navigation keys act on
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+    List,
+    Content,
+}
+
+// Overlays that can be shown on top of the crawler view
+#[derive(Clone, Copy, PartialEq)]
+enum Popup {
+    FullUrl,
+    Info,
+    Summary,
+    Help,
+    Links,          // Interactive list of the selected page's outbound links
+    StructuredData, // JSON-LD found on the selected page
+    Harvest,        // Unique matches of the harvest pattern across every page
+    Queue,          // Pending URLs, editable
+    RecentQueries,  // Search history to pick a filter from
+    Baseline,       // How the crawl differs from the --baseline session
+}
+
+impl AppState {
+    fn new(config: &Config, seed_url: &Url) -> Self {
+        AppState {
+            seed_url: canonicalize_url(seed_url.clone(), config),
+            launch_config: Config {
+                seed_url: Some(seed_url.clone()),
+                ..config.clone()
+            },
+            visited_urls: Vec::new(),
+            body_texts: HashMap::new(),
+            list_state: ListState::default(),
+            search_input: TextInput::default(),
+            active_search_query: String::new(),
+            search_history: Vec::new(),
+            history_state: ListState::default(),
+            is_searching: false,
+            line_input: None,
+            profile_input: None,
+            search_scope: SearchScope::Body,
+            whole_word: false,
+            term_mode: TermMode::Phrase,
+            filtered_url_indices: Vec::new(),
+            content_scroll: 0,
+            content_area: Rect::default(), // Initialize with a default
+            crawl_control: Arc::new(CrawlControl::new()),
+            sessions: Arc::new(SessionPool::new()),
+            popup: None,
+            total_bytes: 0,
+            follow_bottom: false,
+            pin_selection_top: config.pin_selection_top,
+            focus: Focus::List,
+            content_cursor: 0,
+            current_match: None,
+            status_message: None,
+            errors: HashMap::new(),
+            crawl_started_at: Instant::now(),
+            crawl_finished_at: None,
+            crawler_status: None,
+            heartbeat: config.heartbeat,
+            poll_ticks: 0,
+            last_crawler_message: Instant::now(),
+            max_duration: config.max_duration,
+            min_body_chars: config.min_body_chars,
+            page_links: HashMap::new(),
+            links_state: ListState::default(),
+            url_queue: Arc::new(Mutex::new(VecDeque::new())),
+            visited: Arc::new(Mutex::new(HashSet::new())),
+            refresh_pending: HashSet::new(),
+            refresh_total: 0,
+            restart_crawler: false,
+            worker_request: None,
+            worker_count: 1,
+            show_url_list: true,
+            fullscreen_content: false,
+            domain_filter: None,
+            pinned: Vec::new(),
+            json_ld: HashMap::new(),
+            html_sources: HashMap::new(),
+            anchors: HashMap::new(),
+            popup_scroll: 0,
+            depths: HashMap::new(),
+            show_depth_chart: false,
+            show_search_bar: true,
+            show_status_bar: true,
+            harvest_pattern: config.harvest_pattern.clone(),
+            harvested: Vec::new(),
+            newest_first: false,
+            slowest_first: false,
+            view_mode: ViewMode::Flat,
+            tree_depths: Vec::new(),
+            group_sizes: Vec::new(),
+            expanded_groups: HashSet::new(),
+            highlight_color: config.highlight_color,
+            scroll_indicator: config.scroll_indicator,
+            time_display: config.time_display,
+            url_display: config.url_display,
+            auto_select: config.auto_select,
+            awaiting_first_result: true,
+            selection_anchor: None,
+            wrap_lines: false,
+            clean_view: false,
+            full_url_line: false,
+            show_only_matches: false,
+            collapse_repeats: config.collapse_repeats,
+            dim_boilerplate: config.dim_boilerplate,
+            line_frequency: HashMap::new(),
+            counted_pages: 0,
+            match_context: config.match_context,
+            max_render_lines: config.max_render_lines,
+            show_domains: false,
+            titles: HashMap::new(),
+            final_urls: HashMap::new(),
+            content_sources: HashMap::new(),
+            parents: HashMap::new(),
+            show_final_urls: true,
+            crawled_at: HashMap::new(),
+            load_times: HashMap::new(),
+            sitemap_out: config.sitemap_out.clone(),
+            compress_exports: config.compress_exports,
+            clipboard: config.clipboard,
+            list_format: config.list_format.clone(),
+            check_encoding: config.check_encoding,
+            encoding_issues: HashMap::new(),
+            discarded_pages: 0,
+            memory_cap: config.memory_cap,
+            eviction: config.eviction,
+            evicted_urls: Vec::new(),
+            evicted_bytes: 0,
+            queue_snapshot: Vec::new(),
+            queue_state: ListState::default(),
+            baseline: None,
+            baseline_diff: Vec::new(),
+            baseline_state: ListState::default(),
+        }
+    }
+
+    // --- Methods for adding/updating/getting data (mostly unchanged) ---
+    fn add_crawl_result(&mut self, url: String, body: String) {
+        self.count_lines(&body, true);
+        if let Some(existing) = self.body_texts.get_mut(&url) {
+            // A re-crawled page replaces its previous body in place
+            self.total_bytes = self.total_bytes - existing.len() + body.len();
+            let previous = std::mem::replace(existing, body);
+            self.count_lines(&previous, false);
+            self.update_filtered_list();
+            if self.follow_bottom && self.get_selected_url_str() == Some(url.as_str()) {
+                self.scroll_to_bottom();
+            }
+        } else {
+            let is_first_item = self.visited_urls.is_empty();
+            let was_loading = self.is_loading(&url);
+            // A loading row already had its turn at the selection in add_loading_url
+            let auto_select = !was_loading && self.take_auto_select(is_first_item);
+            if !was_loading {
+                self.visited_urls.push(url.clone());
+            }
+            self.total_bytes += body.len();
+            self.body_texts.insert(url.clone(), body);
+            self.update_filtered_list();
+            // --pin-top pulls the selection back only when it moved off the first item
+            let pinned_away = self.pin_selection_top && self.list_state.selected() != Some(0);
+            if pinned_away && !self.filtered_url_indices.is_empty() {
+                self.list_state.select(Some(0));
+                self.reset_or_find_scroll();
+            } else if auto_select && self.select_listed(&url) {
+                self.reset_or_find_scroll();
+            } else if was_loading && self.get_selected_url_str() == Some(url.as_str()) {
+                // The body of the page being looked at just arrived
+                self.reset_or_find_scroll();
+            }
+        }
+    }
+
+    // Lists a URL as soon as the crawler starts on it; its body follows in add_page
+    fn add_loading_url(&mut self, url: String) {
+        if self.body_texts.contains_key(&url) || self.is_loading(&url) {
+            return;
+        }
+        let is_first_item = self.visited_urls.is_empty();
+        let auto_select = self.take_auto_select(is_first_item);
+        self.visited_urls.push(url.clone());
         self.update_filtered_list();
-        self.reset_or_find_scroll();
+        if auto_select {
+            self.select_listed(&url);
+        }
     }
 
-    fn cancel_search(&mut self) {
-        self.is_searching = false;
-        self.search_input.clear();
+    // Whether the page being listed takes the selection, per --auto-select
+    fn take_auto_select(&mut self, is_first_item: bool) -> bool {
+        let first_of_crawl = std::mem::take(&mut self.awaiting_first_result);
+        match self.auto_select {
+            AutoSelect::Always => first_of_crawl || is_first_item,
+            AutoSelect::OnEmpty => is_first_item,
+            AutoSelect::Never => false,
+        }
     }
 
-    fn clear_search(&mut self) {
-        self.is_searching = false;
-        self.search_input.clear();
-        self.active_search_query.clear();
+    // Selects the page if the current filters list it, without changing them
+    fn select_listed(&mut self, url: &str) -> bool {
+        let visited_urls = &self.visited_urls;
+        match self
+            .filtered_url_indices
+            .iter()
+            .position(|&idx| visited_urls[idx] == url)
+        {
+            Some(pos) => {
+                self.list_state.select(Some(pos));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // A page --keep-matching leaves out takes its loading row with it. A re-crawl that no
+    // longer matches keeps the text the page had.
+    fn discard_page(&mut self, url: &str) {
+        self.discarded_pages += 1;
+        self.refresh_pending.remove(url);
+        if !self.is_loading(url) {
+            return;
+        }
+        self.remove_from_list(&[url.to_string()]);
+    }
+
+    // Errors and pins are keyed by position in visited_urls, so the ones after a removed
+    // URL move up. The selection stays on its page unless that page is the one going.
+    fn remove_from_list(&mut self, urls: &[String]) {
+        let selected = self
+            .get_selected_url_str()
+            .filter(|selected| !urls.iter().any(|url| url == selected))
+            .map(str::to_string);
+        let old_urls = std::mem::take(&mut self.visited_urls);
+        let mut new_positions = HashMap::new();
+        for (idx, url) in old_urls.into_iter().enumerate() {
+            if !urls.contains(&url) {
+                new_positions.insert(idx, self.visited_urls.len());
+                self.visited_urls.push(url);
+            }
+        }
+        self.errors = std::mem::take(&mut self.errors)
+            .into_iter()
+            .filter_map(|(idx, error)| Some((*new_positions.get(&idx)?, error)))
+            .collect();
+        self.pinned = std::mem::take(&mut self.pinned)
+            .into_iter()
+            .filter_map(|idx| new_positions.get(&idx).copied())
+            .collect();
         self.update_filtered_list();
-        self.content_scroll = 0; // Reset scroll
+        if let Some(selected) = selected {
+            self.select_listed(&selected);
+        }
     }
 
-    // --- Scrolling Methods ---
-    fn scroll_content_down(&mut self, lines: u16) {
-        self.content_scroll = self.content_scroll.saturating_add(lines);
+    fn toggle_pin(&mut self) {
+        let Some(original_idx) = self.get_selected_original_index() else {
+            return;
+        };
+        let url = self.visited_urls[original_idx].clone();
+        self.status_message = Some(
+            match self.pinned.iter().position(|&idx| idx == original_idx) {
+                Some(pos) => {
+                    self.pinned.remove(pos);
+                    format!("Unpinned {}", url)
+                }
+                None => {
+                    self.pinned.push(original_idx);
+                    format!("Pinned {}: it stays at the top of the list", url)
+                }
+            },
+        );
+        self.update_filtered_list();
     }
 
-    fn scroll_content_up(&mut self, lines: u16) {
-        self.content_scroll = self.content_scroll.saturating_sub(lines);
+    // --- Eviction ---
+    // Drops whole pages, the newest one excepted, until the stored text fits under
+    // --memory-cap-mb. The crawler still counts them as visited, so they aren't
+    // crawled again unless refreshed.
+    fn evict_over_cap(&mut self, newest: &str) {
+        let Some(cap) = self.memory_cap else {
+            return;
+        };
+        let mut evicted = Vec::new();
+        while self.total_bytes - self.evicted_bytes > cap {
+            let Some(url) = self.eviction_candidate(newest, &evicted) else {
+                break;
+            };
+            let body = self.body_texts.remove(&url).unwrap_or_default();
+            self.evicted_bytes += body.len();
+            self.count_lines(&body, false);
+            self.forget_page_details(&url);
+            evicted.push(url);
+        }
+        if !evicted.is_empty() {
+            self.remove_from_list(&evicted);
+            self.evicted_urls.extend(evicted);
+        }
+    }
+
+    // So it's known what the cap cost, since the list no longer shows them
+    fn write_evicted_urls(&mut self) {
+        let mut contents = self.evicted_urls.join("\n");
+        contents.push('\n');
+        self.status_message = Some(
+            match write_export("crabcrawl-evicted.txt", &contents, self.compress_exports) {
+                Ok(path) => format!(
+                    "Evicted {} pages to stay under the memory cap, listed in {}",
+                    self.evicted_urls.len(),
+                    path.display()
+                ),
+                Err(e) => format!("Couldn't list the evicted pages: {}", e),
+            },
+        );
+    }
+
+    fn eviction_candidate(&self, newest: &str, evicted: &[String]) -> Option<String> {
+        let mut stored = self.visited_urls.iter().filter(|url| {
+            *url != newest && self.body_texts.contains_key(*url) && !evicted.contains(url)
+        });
+        match self.eviction {
+            Eviction::Fifo => stored.next(),
+            // min_by_key keeps the first of equals, so ties go in list order
+            Eviction::Oldest => stored.min_by_key(|url| self.crawled_at.get(*url).copied()),
+            Eviction::Largest => stored
+                .rev()
+                .max_by_key(|url| self.body_texts.get(*url).map_or(0, String::len)),
+        }
+        .cloned()
+    }
+
+    // Everything else kept about an evicted page
+    fn forget_page_details(&mut self, url: &str) {
+        self.titles.remove(url);
+        self.page_links.remove(url);
+        self.depths.remove(url);
+        self.parents.remove(url);
+        self.json_ld.remove(url);
+        self.anchors.remove(url);
+        self.html_sources.remove(url);
+        self.crawled_at.remove(url);
+        self.load_times.remove(url);
+        self.final_urls.remove(url);
+        self.content_sources.remove(url);
+        self.encoding_issues.remove(url);
+        self.refresh_pending.remove(url);
+    }
+
+    // Known to the list but still waiting for its body
+    fn is_loading(&self, url: &str) -> bool {
+        !self.body_texts.contains_key(url) && self.visited_urls.iter().any(|known| known == url)
+    }
+
+    fn add_page(&mut self, page: CrawledPage) {
+        // A page that loads on a re-crawl no longer counts as failed
+        if let Some(idx) = self
+            .visited_urls
+            .iter()
+            .position(|known| *known == page.url)
+        {
+            self.errors.remove(&idx);
+        }
+        if page.crawled_at > 0 {
+            self.crawled_at.insert(page.url.clone(), page.crawled_at);
+        }
+        if page.load_ms > 0 {
+            self.load_times.insert(page.url.clone(), page.load_ms);
+        }
+        if let Some(parent) = page.parent {
+            self.parents.insert(page.url.clone(), parent);
+        }
+        match page.final_url {
+            Some(final_url) => self.final_urls.insert(page.url.clone(), final_url),
+            None => self.final_urls.remove(&page.url), // A re-crawl may no longer redirect
+        };
+        match page.content_selector {
+            Some(selector) => self.content_sources.insert(page.url.clone(), selector),
+            None => self.content_sources.remove(&page.url),
+        };
+        self.refresh_pending.remove(&page.url);
+        if self.check_encoding {
+            let replacements = replacement_char_count(&page.body);
+            if looks_mis_decoded(replacements, &page.body) {
+                self.encoding_issues.insert(page.url.clone(), replacements);
+            } else {
+                self.encoding_issues.remove(&page.url);
+            }
+        }
+        // A re-crawl that lost its title mustn't keep matching title searches on the old one
+        if page.title.is_empty() {
+            self.titles.remove(&page.url);
+        } else {
+            self.titles.insert(page.url.clone(), page.title);
+        }
+        self.page_links.insert(page.url.clone(), page.links);
+        self.depths.insert(page.url.clone(), page.depth);
+        if !page.json_ld.is_empty() {
+            self.json_ld.insert(page.url.clone(), page.json_ld);
+        }
+        match page.html {
+            Some(html) => self.html_sources.insert(page.url.clone(), html),
+            None => self.html_sources.remove(&page.url),
+        };
+        if !page.anchors.is_empty() {
+            self.anchors.insert(page.url.clone(), page.anchors);
+        }
+        // Last, so the re-filter in add_crawl_result sees the title, load time and parent
+        // the search, sort and tree view go by
+        let url = page.url.clone();
+        self.add_crawl_result(page.url, page.body);
+        self.evict_over_cap(&url);
+    }
+
+    // A failed page stays in the list with a stand-in body so it can be selected and its
+    // error read in the info popup; a failed re-crawl keeps the body it already had
+    fn add_crawl_failure(&mut self, url: String, error: CrawlError) {
+        self.refresh_pending.remove(&url);
+        if !self.body_texts.contains_key(&url) {
+            self.add_crawl_result(url.clone(), BODY_LOAD_FAILED.to_string());
+        }
+        if let Some(idx) = self.visited_urls.iter().position(|known| *known == url) {
+            self.errors.insert(idx, error);
+        }
+    }
+
+    fn crawl_error(&self, url: &str) -> Option<&CrawlError> {
+        let idx = self.visited_urls.iter().position(|known| known == url)?;
+        self.errors.get(&idx)
+    }
+
+    // Like ] but also explains what went wrong
+    fn focus_next_error(&mut self) {
+        self.select_error_page(true);
+        if self
+            .get_selected_original_index()
+            .is_some_and(|idx| self.is_error_page(idx))
+        {
+            self.popup = Some(Popup::Info);
+        }
+    }
+
+    // Failed to load (now or on its last re-crawl), or its body couldn't be extracted
+    fn is_error_page(&self, original_idx: usize) -> bool {
+        self.errors.contains_key(&original_idx)
+            || self
+                .body_texts
+                .get(&self.visited_urls[original_idx])
+                .is_some_and(|body| is_placeholder_body(body))
+    }
+
+    fn finish_crawl(&mut self, show_summary: bool) {
+        self.crawl_finished_at = Some(Instant::now());
+        if self.sitemap_out.is_some() {
+            self.write_sitemap();
+        }
+        if self.baseline.is_some() {
+            self.compare_with_baseline(); // For the summary
+        }
+        if !self.evicted_urls.is_empty() {
+            self.write_evicted_urls();
+        }
+        if show_summary && self.popup.is_none() {
+            self.popup = Some(Popup::Summary);
+        }
+    }
+
+    fn crawl_time_left(&self) -> Option<Duration> {
+        if self.crawl_finished_at.is_some() {
+            return None;
+        }
+        self.max_duration
+            .map(|limit| limit.saturating_sub(self.crawl_elapsed()))
+    }
+
+    fn crawl_elapsed(&self) -> Duration {
+        self.crawl_finished_at
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.crawl_started_at)
+    }
+
+    // The pages ] steps through: failed ones, including re-crawls that kept their old
+    // body, plus pages whose text couldn't be extracted
+    fn error_count(&self) -> usize {
+        (0..self.visited_urls.len())
+            .filter(|&idx| self.is_error_page(idx))
+            .count()
+    }
+
+    fn unique_domains(&self) -> HashSet<String> {
+        self.visited_urls
+            .iter()
+            .filter_map(|url| Url::parse(url).ok()?.host_str().map(str::to_string))
+            .collect()
+    }
+
+    // Pages per host, busiest first
+    fn domain_page_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for url in &self.visited_urls {
+            if let Ok(parsed) = Url::parse(url)
+                && let Some(host) = parsed.host_str()
+            {
+                *counts.entry(host.to_string()).or_insert(0) += 1;
+            }
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    fn update_filtered_list(&mut self) {
+        let query = self.active_search_query.to_lowercase();
+        let previously_selected_original_index = self.get_selected_original_index();
+
+        self.filtered_url_indices = self
+            .visited_urls
+            .iter()
+            .enumerate()
+            .filter(|(idx, url)| {
+                if self.pinned.contains(idx) {
+                    return false; // Prepended below
+                }
+                let body = self.body_texts.get(*url);
+                let long_enough = match body {
+                    Some(body) => self.passes_body_length_filter(body),
+                    None => self.min_body_chars == 0,
+                };
+                let on_host = self
+                    .domain_filter
+                    .as_ref()
+                    .is_none_or(|host| url_host(url).as_ref() == Some(host));
+                long_enough && on_host && self.matches_query(url, body.map(String::as_str), &query)
+            })
+            .map(|(idx, _url)| idx)
+            .collect();
+        // Stable, so equally slow pages (and those with no time) keep crawl order
+        if self.slowest_first {
+            let load_times = &self.load_times;
+            let visited_urls = &self.visited_urls;
+            self.filtered_url_indices.sort_by_key(|&idx| {
+                std::cmp::Reverse(load_times.get(&visited_urls[idx]).copied().unwrap_or(0))
+            });
+        }
+        if self.newest_first {
+            self.filtered_url_indices.reverse();
+        }
+        self.tree_depths.clear();
+        self.group_sizes.clear();
+        match self.view_mode {
+            ViewMode::Flat => {}
+            ViewMode::Tree => self.arrange_as_tree(),
+            ViewMode::Unique => self.arrange_by_content(),
+        }
+        // Pinned pages head the list at the top level, each a group of its own
+        if !self.pinned.is_empty() {
+            let pinned = self.pinned.len();
+            match self.view_mode {
+                ViewMode::Flat => {}
+                ViewMode::Tree => {
+                    self.tree_depths
+                        .splice(0..0, std::iter::repeat_n(0, pinned));
+                }
+                ViewMode::Unique => {
+                    self.tree_depths
+                        .splice(0..0, std::iter::repeat_n(0, pinned));
+                    self.group_sizes
+                        .splice(0..0, std::iter::repeat_n(1, pinned));
+                }
+            }
+            self.filtered_url_indices
+                .splice(0..0, self.pinned.iter().copied());
+        }
+
+        if let Some(original_idx) = previously_selected_original_index {
+            if let Some(new_filtered_pos) = self
+                .filtered_url_indices
+                .iter()
+                .position(|&idx| idx == original_idx)
+            {
+                self.list_state.select(Some(new_filtered_pos));
+            } else {
+                self.select_first_or_last();
+            }
+        } else if self.auto_select != AutoSelect::Never {
+            self.select_first_or_last();
+        }
+    }
+
+    // Reorders the filtered list depth-first under each page's parent, keeping the current
+    // order among siblings. Pages whose parent isn't listed start a tree of their own.
+    fn arrange_as_tree(&mut self) {
+        let listed: HashMap<&str, usize> = self
+            .filtered_url_indices
+            .iter()
+            .map(|&idx| (self.visited_urls[idx].as_str(), idx))
+            .collect();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for &idx in &self.filtered_url_indices {
+            let parent = self
+                .parents
+                .get(&self.visited_urls[idx])
+                .and_then(|parent| listed.get(parent.as_str()));
+            match parent {
+                Some(&parent) if parent != idx => children.entry(parent).or_default().push(idx),
+                _ => roots.push(idx),
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.filtered_url_indices.len());
+        let mut depths = Vec::with_capacity(self.filtered_url_indices.len());
+        let mut placed = HashSet::new();
+        // Pushed in reverse so they come off the stack in list order
+        let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&idx| (idx, 0)).collect();
+        loop {
+            while let Some((idx, depth)) = stack.pop() {
+                if !placed.insert(idx) {
+                    continue;
+                }
+                order.push(idx);
+                depths.push(depth);
+                if let Some(children) = children.get(&idx) {
+                    stack.extend(children.iter().rev().map(|&child| (child, depth + 1)));
+                }
+            }
+            // Pages whose parents link back to them have no root; list them rather than lose them
+            match self
+                .filtered_url_indices
+                .iter()
+                .find(|idx| !placed.contains(*idx))
+            {
+                Some(&idx) => stack.push((idx, 0)),
+                None => break,
+            }
+        }
+        self.filtered_url_indices = order;
+        self.tree_depths = depths;
+    }
+
+    // Hash of a page's body for the unique view; pages without real content aren't grouped
+    fn content_hash(&self, idx: usize) -> Option<u64> {
+        self.body_texts
+            .get(&self.visited_urls[idx])
+            .filter(|body| !is_placeholder_body(body))
+            .map(|body| body_hash(body))
+    }
+
+    // Keeps the first page of each distinct body, in the current order, followed by the
+    // rest of its group when that group is expanded
+    fn arrange_by_content(&mut self) {
+        let mut groups: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut rows = Vec::new();
+        for &idx in &self.filtered_url_indices {
+            match self.content_hash(idx) {
+                Some(hash) => {
+                    let members = groups.entry(hash).or_default();
+                    if members.is_empty() {
+                        rows.push((idx, Some(hash)));
+                    }
+                    members.push(idx);
+                }
+                None => rows.push((idx, None)),
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut depths = Vec::new();
+        let mut sizes = Vec::new();
+        for (idx, hash) in rows {
+            let members = hash.and_then(|hash| groups.get(&hash).map(|members| (hash, members)));
+            order.push(idx);
+            depths.push(0);
+            sizes.push(members.map_or(1, |(_, members)| members.len()));
+            if let Some((hash, members)) = members
+                && self.expanded_groups.contains(&hash)
+            {
+                for &member in &members[1..] {
+                    order.push(member);
+                    depths.push(1);
+                    sizes.push(0);
+                }
+            }
+        }
+        self.filtered_url_indices = order;
+        self.tree_depths = depths;
+        self.group_sizes = sizes;
+    }
+
+    // Expands or folds the selected page's group in the unique view
+    fn toggle_content_group(&mut self) {
+        if self.view_mode != ViewMode::Unique {
+            return;
+        }
+        let Some(hash) = self
+            .list_state
+            .selected()
+            .and_then(|pos| self.filtered_url_indices.get(pos))
+            .and_then(|&idx| self.content_hash(idx))
+        else {
+            return;
+        };
+        // The group's first row is its representative; folding from a member lands there
+        let Some(first) = self
+            .filtered_url_indices
+            .iter()
+            .position(|&idx| self.content_hash(idx) == Some(hash))
+        else {
+            return;
+        };
+        if self.group_sizes.get(first).copied().unwrap_or(1) < 2 {
+            self.status_message = Some("No other page has this content".to_string());
+            return;
+        }
+        self.list_state.select(Some(first));
+        if !self.expanded_groups.remove(&hash) {
+            self.expanded_groups.insert(hash);
+        }
+        self.update_filtered_list();
+    }
+
+    fn toggle_view_mode(&mut self) {
+        self.view_mode = match self.view_mode {
+            ViewMode::Flat => ViewMode::Tree,
+            ViewMode::Tree => ViewMode::Unique,
+            ViewMode::Unique => ViewMode::Flat,
+        };
+        self.update_filtered_list();
+        self.status_message = Some(
+            match self.view_mode {
+                ViewMode::Flat => "Flat list",
+                ViewMode::Tree => "Tree view: each page under the page that linked to it",
+                ViewMode::Unique => "Unique content: one row per distinct body, Enter expands",
+            }
+            .to_string(),
+        );
+    }
+
+    // Whether the page matches the lowercased query in the current search scope
+    fn matches_query(&self, url: &str, body: Option<&str>, query: &str) -> bool {
+        if query.is_empty() {
+            return true;
+        }
+        let haystack = match self.search_scope {
+            SearchScope::Body => body,
+            SearchScope::Url => Some(url),
+            SearchScope::Title => self.titles.get(url).map(String::as_str),
+        };
+        haystack.is_some_and(|text| self.contains_query(text, query))
+    }
+
+    fn contains_query(&self, text: &str, query_lower: &str) -> bool {
+        self.contains_terms(text, query_lower, self.term_mode == TermMode::All)
+    }
+
+    // A line only needs one word of an all-words search, so each of them gets jumped to
+    fn line_has_match(&self, line: &str, query_lower: &str) -> bool {
+        self.contains_terms(line, query_lower, false)
+    }
+
+    fn contains_terms(&self, text: &str, query_lower: &str, require_all: bool) -> bool {
+        let text_lower = text.to_lowercase();
+        let mut terms = search_terms(query_lower, self.term_mode).into_iter();
+        let found = |term: &str| {
+            query_matches(&text_lower, term, self.whole_word)
+                .next()
+                .is_some()
+        };
+        if require_all {
+            terms.all(found)
+        } else {
+            terms.any(found)
+        }
+    }
+
+    fn cycle_term_mode(&mut self) {
+        self.term_mode = match self.term_mode {
+            TermMode::Phrase => TermMode::Any,
+            TermMode::Any => TermMode::All,
+            TermMode::All => TermMode::Phrase,
+        };
+        self.update_filtered_list();
+        self.reset_or_find_scroll();
+        self.status_message = Some(
+            match self.term_mode {
+                TermMode::Phrase => "Search matches the query as one phrase",
+                TermMode::Any => "Search matches pages with any of the words",
+                TermMode::All => "Search matches pages with all of the words",
+            }
+            .to_string(),
+        );
+    }
+
+    fn toggle_whole_word(&mut self) {
+        self.whole_word = !self.whole_word;
+        self.update_filtered_list();
+        self.reset_or_find_scroll();
+    }
+
+    // The query to highlight and jump to inside the content; only body searches have one
+    fn content_query(&self) -> &str {
+        match self.search_scope {
+            SearchScope::Body => &self.active_search_query,
+            SearchScope::Url | SearchScope::Title => "",
+        }
+    }
+
+    fn cycle_search_scope(&mut self) {
+        self.search_scope = match self.search_scope {
+            SearchScope::Body => SearchScope::Title,
+            SearchScope::Title => SearchScope::Url,
+            SearchScope::Url => SearchScope::Body,
+        };
+        self.update_filtered_list();
+        self.reset_or_find_scroll();
+    }
+
+    fn passes_body_length_filter(&self, body: &str) -> bool {
+        self.min_body_chars == 0
+            || (!is_placeholder_body(body) && body.chars().count() >= self.min_body_chars)
+    }
+
+    fn adjust_min_body_chars(&mut self, increase: bool) {
+        self.min_body_chars = if increase {
+            self.min_body_chars.saturating_add(MIN_BODY_CHARS_STEP)
+        } else {
+            self.min_body_chars.saturating_sub(MIN_BODY_CHARS_STEP)
+        };
+        self.update_filtered_list();
+        self.reset_or_find_scroll();
+    }
+
+    fn select_first_or_last(&mut self) {
+        if !self.filtered_url_indices.is_empty() {
+            self.list_state.select(Some(0));
+        } else {
+            self.list_state.select(None);
+        }
+    }
+
+    fn get_selected_original_index(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|selected_filtered_idx| self.filtered_url_indices.get(selected_filtered_idx))
+            .copied()
+    }
+
+    fn get_displayed_urls(&self) -> Vec<&String> {
+        self.filtered_url_indices
+            .iter()
+            .map(|&idx| &self.visited_urls[idx])
+            .collect()
+    }
+
+    fn get_selected_content(&self) -> Option<&String> {
+        self.get_selected_original_index()
+            .and_then(|original_idx| self.visited_urls.get(original_idx))
+            .and_then(|url| self.body_texts.get(url))
+    }
+
+    fn get_selected_url_str(&self) -> Option<&str> {
+        self.get_selected_original_index()
+            .map(|original_idx| self.visited_urls[original_idx].as_str())
+    }
+
+    fn find_first_match_line(&self) -> Option<u16> {
+        if self.content_query().is_empty() {
+            return None;
+        }
+        let query_lower = self.content_query().to_lowercase();
+        self.get_selected_content().and_then(|content| {
+            content
+                .lines()
+                .position(|line| self.line_has_match(line, &query_lower))
+                .map(|line_idx| line_idx as u16)
+        })
+    }
+
+    // Line indices of the selected content that contain the active search query.
+    fn find_match_lines(&self) -> Vec<usize> {
+        if self.content_query().is_empty() {
+            return Vec::new();
+        }
+        let query_lower = self.content_query().to_lowercase();
+        self.get_selected_content()
+            .map_or_else(Vec::new, |content| {
+                content
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, line)| self.line_has_match(line, &query_lower))
+                    .map(|(line_idx, _)| line_idx)
+                    .collect()
+            })
+    }
+
+    fn reset_or_find_scroll(&mut self) {
+        if self.follow_bottom {
+            self.scroll_to_bottom();
+        } else if !self.content_query().is_empty() {
+            let line = self.find_first_match_line().unwrap_or(0);
+            self.content_scroll = self.row_of_line(line as usize).min(u16::MAX as usize) as u16;
+        } else {
+            self.content_scroll = 0;
+        }
+        self.content_cursor = self
+            .line_at_row(self.content_scroll as usize)
+            .min(u16::MAX as usize) as u16;
+        self.selection_anchor = None;
+        // Scrolled to the first match, so n goes on to the second
+        self.current_match =
+            (!self.follow_bottom && self.find_first_match_line().is_some()).then_some(0);
+    }
+
+    // The line of every match on the selected page in order, a line once per match on it
+    fn match_positions(&self) -> Vec<usize> {
+        let query_lower = self.content_query().to_lowercase();
+        let Some(content) = self.get_selected_content() else {
+            return Vec::new();
+        };
+        if query_lower.is_empty() {
+            return Vec::new();
+        }
+        let terms = search_terms(&query_lower, self.term_mode);
+        content
+            .lines()
+            .enumerate()
+            .flat_map(|(idx, line)| {
+                let matches = term_matches(&line.to_lowercase(), &terms, self.whole_word).len();
+                std::iter::repeat_n(idx, matches)
+            })
+            .collect()
+    }
+
+    // n/N: puts the cursor on the next or previous match, wrapping around the page
+    fn jump_to_match(&mut self, forward: bool) {
+        let positions = self.match_positions();
+        if positions.is_empty() {
+            self.status_message = Some(if self.content_query().is_empty() {
+                "No body search to jump between matches of".to_string()
+            } else {
+                "No matches on this page".to_string()
+            });
+            return;
+        }
+        let count = positions.len();
+        let cursor = self.content_cursor as usize;
+        let next = match self.current_match.filter(|&current| current < count) {
+            Some(current) if forward => (current + 1) % count,
+            Some(current) => (current + count - 1) % count,
+            // Nothing visited yet: start from the cursor
+            None if forward => positions
+                .iter()
+                .position(|&line| line >= cursor)
+                .unwrap_or(0),
+            None => positions
+                .iter()
+                .rposition(|&line| line <= cursor)
+                .unwrap_or(count - 1),
+        };
+        self.current_match = Some(next);
+        self.content_cursor = positions[next].min(u16::MAX as usize) as u16;
+        self.selection_anchor = None;
+        self.scroll_to_cursor();
+        self.status_message = Some(format!("Match {}/{}", next + 1, count));
+    }
+
+    // --- Methods for UI State Manipulation (mostly unchanged) ---
+    fn select_next(&mut self) {
+        let len = self.filtered_url_indices.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) => (i + 1) % len,
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+        self.reset_or_find_scroll();
+    }
+
+    fn select_previous(&mut self) {
+        let len = self.filtered_url_indices.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+        self.reset_or_find_scroll();
+    }
+
+    fn select_seed(&mut self) {
+        let seed_url = self.seed_url.clone();
+        if !self.visited_urls.contains(&seed_url) {
+            self.status_message = Some("The seed URL hasn't been crawled yet".to_string());
+        } else if !self.select_url(&seed_url) {
+            self.status_message = Some(format!("{} is hidden by the filters", seed_url));
+        }
+    }
+
+    // Walks one step back up the crawl tree, to the page whose link led to the selected one
+    fn select_parent(&mut self) {
+        let Some(url) = self.get_selected_url_str() else {
+            return;
+        };
+        let Some(parent) = self.parents.get(url).cloned() else {
+            self.status_message = Some(if *url == self.seed_url {
+                "The seed URL has no parent page".to_string()
+            } else {
+                format!("Don't know which page linked to {}", url)
+            });
+            return;
+        };
+        if !self.select_url(&parent) {
+            self.status_message = Some(format!("{} isn't in the list", parent));
+        }
+    }
+
+    // Moves to the next (or previous) listed page that failed or whose body couldn't be
+    // extracted, wrapping
+    fn select_error_page(&mut self, forward: bool) {
+        let len = self.filtered_url_indices.len();
+        let current = self.list_state.selected();
+        let is_error = |pos: usize| self.is_error_page(self.filtered_url_indices[pos]);
+        let found = (1..=len)
+            .map(|step| match current {
+                Some(i) if forward => (i + step) % len,
+                Some(i) => (i + len - step % len) % len,
+                None => (step - 1) % len,
+            })
+            .find(|&pos| is_error(pos));
+        match found {
+            Some(pos) => {
+                self.list_state.select(Some(pos));
+                self.reset_or_find_scroll();
+            }
+            None => self.status_message = Some("No listed pages with errors".to_string()),
+        }
+    }
+
+    // Not used. Could be useful in future scenarios where we want to select the last item in the list.
+    // fn select_last(&mut self) {
+    //     let len = self.filtered_url_indices.len();
+    //     if len > 0 {
+    //         self.list_state.select(Some(len - 1));
+    //     } else {
+    //         self.list_state.select(None);
+    //     }
+    //     self.reset_or_find_scroll(); // Ensure scroll updates even when selecting last
+    // }
+
+    fn start_search(&mut self) {
+        self.is_searching = true;
+        self.search_input.set(self.active_search_query.clone());
+    }
+
+    fn finalize_search(&mut self) {
+        self.is_searching = false;
+        self.active_search_query = self.search_input.as_str().to_string();
+        self.remember_query();
+        self.update_filtered_list();
+        self.reset_or_find_scroll();
+    }
+
+    // --- Search History ---
+    // A repeated query moves back to the top instead of being listed twice
+    fn remember_query(&mut self) {
+        let query = self.active_search_query.clone();
+        if query.is_empty() {
+            return;
+        }
+        self.search_history.retain(|previous| *previous != query);
+        self.search_history.insert(0, query);
+        self.search_history.truncate(SEARCH_HISTORY_LEN);
+    }
+
+    fn show_recent_queries(&mut self) {
+        self.is_searching = false;
+        if self.search_history.is_empty() {
+            self.status_message = Some("No searches yet".to_string());
+            return;
+        }
+        self.history_state.select(Some(0));
+        self.popup = Some(Popup::RecentQueries);
+    }
+
+    fn select_next_query(&mut self) {
+        let len = self.search_history.len();
+        if len > 0 {
+            let i = self.history_state.selected().map_or(0, |i| (i + 1) % len);
+            self.history_state.select(Some(i));
+        }
+    }
+
+    fn select_previous_query(&mut self) {
+        let len = self.search_history.len();
+        if len > 0 {
+            let i = self
+                .history_state
+                .selected()
+                .map_or(0, |i| (i + len - 1) % len);
+            self.history_state.select(Some(i));
+        }
+    }
+
+    fn apply_selected_query(&mut self) {
+        self.popup = None;
+        let Some(query) = self
+            .history_state
+            .selected()
+            .and_then(|i| self.search_history.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        self.search_input.set(query.clone());
+        self.active_search_query = query;
+        self.remember_query();
+        self.update_filtered_list();
+        self.reset_or_find_scroll();
+    }
+
+    fn cancel_search(&mut self) {
+        self.is_searching = false;
+        self.search_input.clear();
+    }
+
+    fn clear_search(&mut self) {
+        self.is_searching = false;
+        self.search_input.clear();
+        self.active_search_query.clear();
+        self.update_filtered_list();
+        self.content_scroll = 0; // Reset scroll
+    }
+
+    // --- Scrolling Methods ---
+    fn scroll_content_down(&mut self, lines: u16) {
+        // Never scroll past the end, but don't yank back a jump to a late search match either
+        let max_scroll = self.max_content_scroll().max(self.content_scroll);
+        self.content_scroll = self.content_scroll.saturating_add(lines).min(max_scroll);
+    }
+
+    fn scroll_to_bottom(&mut self) {
+        self.content_scroll = self.max_content_scroll();
+    }
+
+    // Largest useful scroll offset: the last row sits at the bottom of the content panel
+    fn max_content_scroll(&self) -> u16 {
+        let visible_lines = self.content_area.height.saturating_sub(2) as usize; // Minus borders
+        self.total_content_rows()
+            .saturating_sub(visible_lines)
+            .min(u16::MAX as usize) as u16
+    }
+
+    // --- Word Wrap ---
+    // With wrapping on, content_scroll counts visual rows while the cursor, selection and
+    // search matches stay in source lines; these convert between the two.
+    fn toggle_wrap(&mut self) {
+        let top_line = self.line_at_row(self.content_scroll as usize);
+        self.wrap_lines = !self.wrap_lines;
+        self.content_scroll = self.row_of_line(top_line).min(u16::MAX as usize) as u16;
+        self.scroll_to_cursor();
+    }
+
+    // Clean view only changes what's drawn; body_texts keeps the page as scraped, and the
+    // line count stays the same so the cursor and selection still line up
+    fn toggle_clean_view(&mut self) {
+        let top_line = self.line_at_row(self.content_scroll as usize);
+        self.clean_view = !self.clean_view;
+        self.content_scroll = self.row_of_line(top_line).min(u16::MAX as usize) as u16;
+        self.scroll_to_cursor();
+    }
+
+    fn toggle_full_url_line(&mut self) {
+        self.full_url_line = !self.full_url_line;
+        self.status_message = Some(
+            if self.full_url_line {
+                "Long URLs shown in full above the content"
+            } else {
+                "Long URLs cut off in the content title"
+            }
+            .to_string(),
+        );
+    }
+
+    fn displayed_content(&self) -> Option<Cow<'_, str>> {
+        let content = self.get_selected_content()?;
+        Some(if self.clean_view {
+            Cow::Owned(clean_up_indentation(content))
+        } else {
+            Cow::Borrowed(content.as_str())
+        })
+    }
+
+    // --- Only Matching Lines ---
+    // Like grep -C: with a body search active, lines away from every match are hidden and
+    // each gap is drawn as a "--" row. Hidden lines take no rows, so the cursor, selection
+    // and yanks keep working in source lines.
+    fn toggle_only_matches(&mut self) {
+        let top_line = self.line_at_row(self.content_scroll as usize);
+        self.show_only_matches = !self.show_only_matches;
+        // A cursor on a line that just disappeared moves to the next one still shown
+        if let Some(visible) = self.visible_lines() {
+            let cursor = self.content_cursor as usize;
+            if let Some(line) = (cursor..visible.len()).find(|&line| visible[line]) {
+                self.content_cursor = line.min(u16::MAX as usize) as u16;
+            }
+        }
+        self.content_scroll = self.row_of_line(top_line).min(u16::MAX as usize) as u16;
+        self.scroll_to_cursor();
+        self.status_message = Some(
+            match (self.show_only_matches, self.content_query().is_empty()) {
+                (true, true) => "Only matching lines once a body search is active",
+                (true, false) => "Showing only matching lines",
+                (false, _) => "Showing all lines",
+            }
+            .to_string(),
+        );
+    }
+
+    // Source lines kept by the grep view, or None when it isn't filtering
+    fn match_filter(&self) -> Option<Vec<bool>> {
+        if !self.show_only_matches || self.content_query().is_empty() {
+            return None;
+        }
+        let line_count = self.get_selected_content()?.lines().count();
+        let mut visible = vec![false; line_count];
+        for line in self.find_match_lines() {
+            let last = (line + self.match_context).min(line_count.saturating_sub(1));
+            for shown in &mut visible[line.saturating_sub(self.match_context)..=last] {
+                *shown = true;
+            }
+        }
+        Some(visible)
+    }
+
+    // Which source lines are shown, or None when nothing is hidden
+    fn visible_lines(&self) -> Option<Vec<bool>> {
+        let filter = self.match_filter();
+        let repeats = self.repeat_counts();
+        if filter.is_none() && repeats.is_none() {
+            return None;
+        }
+        let line_count = self.get_selected_content()?.lines().count();
+        Some(
+            (0..line_count)
+                .map(|idx| {
+                    filter.as_ref().is_none_or(|filter| filter[idx])
+                        && repeats.as_ref().is_none_or(|repeats| repeats[idx] > 0)
+                })
+                .collect(),
+        )
+    }
+
+    // --- Collapsed Repeats ---
+    // With z on, each run of identical consecutive lines is drawn once with a (×N)
+    // marker. The body stays as it is; the repeats are hidden like grep-view lines.
+    fn toggle_collapse_repeats(&mut self) {
+        let top_line = self.line_at_row(self.content_scroll as usize);
+        self.collapse_repeats = !self.collapse_repeats;
+        // A cursor inside a run moves back to the line left standing for it
+        if let Some(visible) = self.visible_lines() {
+            let cursor = (self.content_cursor as usize).min(visible.len().saturating_sub(1));
+            if let Some(line) = (0..=cursor).rev().find(|&line| visible[line]) {
+                self.content_cursor = line.min(u16::MAX as usize) as u16;
+            }
+        }
+        self.content_scroll = self.row_of_line(top_line).min(u16::MAX as usize) as u16;
+        self.scroll_to_cursor();
+        self.status_message = Some(if self.collapse_repeats {
+            "Collapsing repeated lines".to_string()
+        } else {
+            "Showing repeated lines".to_string()
+        });
+    }
+
+    // Lines each source line stands for: the run length on the first line of a run of
+    // identical lines, 0 on the repeats folded into it. None when collapsing is off.
+    fn repeat_counts(&self) -> Option<Vec<usize>> {
+        if !self.collapse_repeats {
+            return None;
+        }
+        let content = self.displayed_content()?;
+        let lines: Vec<&str> = content.lines().collect();
+        // Lines on either side of a grep-view gap never belong to the same run
+        let filter = self.match_filter();
+        let kept = |idx: usize| filter.as_ref().is_none_or(|filter| filter[idx]);
+        let mut counts = vec![1; lines.len()];
+        let mut run_start = 0;
+        for idx in 1..lines.len() {
+            if kept(idx) && kept(idx - 1) && lines[idx] == lines[idx - 1] {
+                counts[idx] = 0;
+                counts[run_start] += 1;
+            } else {
+                run_start = idx;
+            }
+        }
+        Some(counts)
+    }
+
+    // Visual rows taken by each source line of the selected content
+    fn line_heights(&self) -> Vec<usize> {
+        // Minus the borders and the scrollbar column
+        let width = self.content_area.width.saturating_sub(3) as usize;
+        let Some(content) = self.displayed_content() else {
+            return Vec::new();
+        };
+        let visible = self.visible_lines();
+        let repeats = self.repeat_counts();
+        let mut any_shown = false;
+        content
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                let count = repeats.as_ref().map_or(1, |repeats| repeats[idx]);
+                let mut height = match (self.wrap_lines, count > 1) {
+                    (true, true) => {
+                        wrapped_row_count(&format!("{line}{}", repeat_marker(count)), width)
+                    }
+                    (true, false) => wrapped_row_count(line, width),
+                    (false, _) => 1,
+                };
+                if let Some(visible) = &visible {
+                    if !visible[idx] {
+                        return 0;
+                    }
+                    // The "--" row drawn before every group of lines but the first
+                    if any_shown && grep_gap_before(visible, repeats.as_deref(), idx) {
+                        height += 1;
+                    }
+                    any_shown = true;
+                }
+                height
+            })
+            .collect()
+    }
+
+    fn total_content_rows(&self) -> usize {
+        self.line_heights().iter().sum()
+    }
+
+    fn row_of_line(&self, line: usize) -> usize {
+        if !self.wrap_lines && !self.show_only_matches && !self.collapse_repeats {
+            return line;
+        }
+        self.line_heights().iter().take(line).sum()
+    }
+
+    // Source line shown at a visual row (the last line for rows past the end)
+    fn line_at_row(&self, row: usize) -> usize {
+        if !self.wrap_lines && !self.show_only_matches && !self.collapse_repeats {
+            return row;
+        }
+        let heights = self.line_heights();
+        let mut line_start = 0;
+        for (line, height) in heights.iter().enumerate() {
+            line_start += height;
+            if row < line_start {
+                return line;
+            }
+        }
+        heights.len().saturating_sub(1)
+    }
+
+    // --- Boilerplate ---
+    // Kept up to date as pages arrive, are re-crawled and are evicted, so dimming costs a
+    // lookup per line when drawing
+    fn count_lines(&mut self, body: &str, adding: bool) {
+        if is_placeholder_body(body) {
+            return;
+        }
+        let lines: HashSet<u64> = body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(body_hash)
+            .collect();
+        for hash in lines {
+            let count = self.line_frequency.entry(hash).or_insert(0);
+            if adding {
+                *count += 1;
+            } else {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    self.line_frequency.remove(&hash);
+                }
+            }
+        }
+        if adding {
+            self.counted_pages += 1;
+        } else {
+            self.counted_pages = self.counted_pages.saturating_sub(1);
+        }
+    }
+
+    // On at least BOILERPLATE_MIN_PAGES pages and a good share of all of them
+    fn is_boilerplate_line(&self, line: &str) -> bool {
+        let line = line.trim();
+        if line.is_empty() {
+            return false;
+        }
+        let pages = self
+            .line_frequency
+            .get(&body_hash(line))
+            .copied()
+            .unwrap_or(0);
+        pages >= BOILERPLATE_MIN_PAGES && pages * BOILERPLATE_SHARE >= self.counted_pages
+    }
+
+    fn toggle_dim_boilerplate(&mut self) {
+        self.dim_boilerplate = !self.dim_boilerplate;
+        self.status_message = Some(if self.dim_boilerplate {
+            "Dimming lines shared by many pages".to_string()
+        } else {
+            "Showing every line at full brightness".to_string()
+        });
+    }
+
+    // --- Content Cursor ---
+    fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::List => Focus::Content,
+            Focus::Content => Focus::List,
+        };
+        self.selection_anchor = None;
+    }
+
+    // Visual-line mode: the range runs from the anchor to wherever the cursor moves
+    fn toggle_line_selection(&mut self) {
+        self.selection_anchor = match self.selection_anchor {
+            Some(_) => None,
+            None => Some(self.content_cursor),
+        };
+    }
+
+    // Inclusive line range between the anchor and the cursor
+    fn selected_line_range(&self) -> Option<(u16, u16)> {
+        self.selection_anchor.map(|anchor| {
+            (
+                anchor.min(self.content_cursor),
+                anchor.max(self.content_cursor),
+            )
+        })
+    }
+
+    fn yank_selection(&mut self) {
+        let Some((first, last)) = self.selected_line_range() else {
+            return;
+        };
+        let lines: Vec<&str> = self
+            .get_selected_content()
+            .map(|content| {
+                content
+                    .lines()
+                    .skip(first as usize)
+                    .take((last - first) as usize + 1)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let message = match copy_to_clipboard(&lines.join("\n"), self.clipboard) {
+            Ok(destination) => format!(
+                "Copied lines {}-{} ({} lines) to {}",
+                first + 1,
+                last + 1,
+                lines.len(),
+                destination
+            ),
+            Err(e) => format!("Copy failed: {}", e),
+        };
+        self.status_message = Some(message);
+        self.selection_anchor = None;
+    }
+
+    // --- Profiles ---
+    fn start_profile_input(&mut self) {
+        self.profile_input = Some(TextInput::default());
+    }
+
+    fn save_profile(&mut self) {
+        let Some(input) = self.profile_input.take() else {
+            return;
+        };
+        let name = input.as_str().trim();
+        if name.is_empty() {
+            return;
+        }
+        self.status_message = Some(match save_profile(name, &self.effective_config()) {
+            Ok(path) => format!(
+                "Saved profile {} to {}, load it with --profile {}",
+                name,
+                path.display(),
+                name
+            ),
+            Err(e) => format!("Saving the profile failed: {}", e),
+        });
+    }
+
+    // --- Go To Line ---
+    fn start_line_input(&mut self) {
+        if self.get_selected_content().is_some() {
+            self.line_input = Some(TextInput::default());
+        }
+    }
+
+    // Puts the cursor on a 1-based line, clamped to the page, at the top of the panel
+    fn jump_to_line(&mut self) {
+        let Some(input) = self.line_input.take() else {
+            return;
+        };
+        let Ok(line) = input.as_str().parse::<usize>() else {
+            return;
+        };
+        let line_count = self
+            .get_selected_content()
+            .map_or(0, |content| content.lines().count());
+        if line_count == 0 {
+            return;
+        }
+        let line = line.clamp(1, line_count) - 1;
+        self.focus = Focus::Content;
+        self.selection_anchor = None;
+        self.content_cursor = line.min(u16::MAX as usize) as u16;
+        self.content_scroll = self
+            .row_of_line(line)
+            .min(self.max_content_scroll() as usize) as u16;
+        self.status_message = Some(format!("Line {} of {}", line + 1, line_count));
+    }
+
+    fn move_content_cursor_down(&mut self) {
+        let last_line = self
+            .get_selected_content()
+            .map_or(0, |content| content.lines().count().saturating_sub(1));
+        let cursor = self.content_cursor as usize;
+        let next = match self.visible_lines() {
+            Some(visible) => (cursor + 1..visible.len())
+                .find(|&line| visible[line])
+                .unwrap_or(cursor),
+            None => (cursor + 1).min(last_line),
+        };
+        self.content_cursor = next.min(u16::MAX as usize) as u16;
+        self.scroll_to_cursor();
+    }
+
+    fn move_content_cursor_up(&mut self) {
+        let cursor = self.content_cursor as usize;
+        let previous = match self.visible_lines() {
+            Some(visible) => (0..cursor)
+                .rev()
+                .find(|&line| visible[line])
+                .unwrap_or(cursor),
+            None => cursor.saturating_sub(1),
+        };
+        self.content_cursor = previous as u16;
+        self.scroll_to_cursor();
+    }
+
+    // Keep the cursor line inside the visible part of the content panel
+    fn scroll_to_cursor(&mut self) {
+        let visible_rows = self.content_area.height.saturating_sub(2).max(1) as usize;
+        let cursor = self.content_cursor as usize;
+        let first_row = self.row_of_line(cursor);
+        let last_row = first_row + self.line_heights().get(cursor).copied().unwrap_or(1) - 1;
+        let scroll = self.content_scroll as usize;
+        let new_scroll = if first_row < scroll {
+            first_row
+        } else if last_row >= scroll + visible_rows {
+            // A line taller than the panel shows from its first row
+            (last_row + 1 - visible_rows).min(first_row)
+        } else {
+            scroll
+        };
+        self.content_scroll = new_scroll.min(u16::MAX as usize) as u16;
+    }
+
+    fn yank_cursor_line(&mut self) {
+        let line = self
+            .get_selected_content()
+            .and_then(|content| content.lines().nth(self.content_cursor as usize))
+            .map(str::to_string);
+        self.status_message = Some(match line {
+            Some(line) => match copy_to_clipboard(&line, self.clipboard) {
+                Ok(destination) => format!(
+                    "Copied line {} to {}: {}",
+                    self.content_cursor + 1,
+                    destination,
+                    line.trim()
+                ),
+                Err(e) => format!("Copy failed: {}", e),
+            },
+            None => "Nothing to copy: cursor is past the end of the page".to_string(),
+        });
+    }
+
+    fn copy_page_html(&mut self) {
+        let Some(url) = self.get_selected_url_str() else {
+            return;
+        };
+        let message = match self.html_sources.get(url) {
+            Some(html) => match copy_to_clipboard(html, self.clipboard) {
+                Ok(destination) => {
+                    format!("Copied {} bytes of HTML to {}", html.len(), destination)
+                }
+                Err(e) => format!("Copy failed: {}", e),
+            },
+            None if !self.launch_config.capture_html => {
+                "No HTML kept: crawl with --capture-html to copy pages' HTML".to_string()
+            }
+            None => "No HTML captured for this page".to_string(),
+        };
+        self.status_message = Some(message);
+    }
+
+    fn toggle_depth_chart(&mut self) {
+        self.show_depth_chart = !self.show_depth_chart;
+    }
+
+    fn toggle_domains_panel(&mut self) {
+        self.show_domains = !self.show_domains;
+    }
+
+    // Number of crawled pages at each depth, from the seed (depth 0) down to the deepest page
+    fn depth_histogram(&self) -> Vec<u64> {
+        let mut histogram = Vec::new();
+        for url in &self.visited_urls {
+            if let Some(&depth) = self.depths.get(url) {
+                if histogram.len() <= depth {
+                    histogram.resize(depth + 1, 0);
+                }
+                histogram[depth] += 1;
+            }
+        }
+        histogram
+    }
+
+    fn toggle_url_list(&mut self) {
+        self.show_url_list = !self.show_url_list;
+        if !self.show_url_list {
+            // j/k keep switching pages while the list is out of sight
+            self.focus = Focus::List;
+        }
+    }
+
+    // Narrows the list to the selected page's host, or back to every host
+    fn toggle_domain_filter(&mut self) {
+        if self.domain_filter.take().is_none() {
+            let Some(host) = self.get_selected_url_str().and_then(url_host) else {
+                return;
+            };
+            self.domain_filter = Some(host);
+        }
+        self.update_filtered_list();
+        self.status_message = Some(match &self.domain_filter {
+            Some(host) => format!(
+                "Only pages on {} ({}), h for every host",
+                host,
+                self.filtered_url_indices.len()
+            ),
+            None => "Pages on every host".to_string(),
+        });
+    }
+
+    fn toggle_fullscreen_content(&mut self) {
+        self.fullscreen_content = !self.fullscreen_content;
+        if self.fullscreen_content {
+            // As with the list hidden, j/k go on switching pages
+            self.focus = Focus::List;
+        }
+    }
+
+    // Flips the list order; update_filtered_list keeps the same page selected
+    fn toggle_list_order(&mut self) {
+        self.newest_first = !self.newest_first;
+        self.update_filtered_list();
+        self.status_message = Some(
+            if self.newest_first {
+                "Newest pages first"
+            } else {
+                "Oldest pages first"
+            }
+            .to_string(),
+        );
+    }
+
+    fn toggle_slowest_first(&mut self) {
+        self.slowest_first = !self.slowest_first;
+        self.update_filtered_list();
+        self.status_message = Some(
+            if self.slowest_first {
+                "Sorted by load time, slowest first"
+            } else {
+                "Sorted in crawl order"
+            }
+            .to_string(),
+        );
+    }
+
+    fn toggle_pin_selection_top(&mut self) {
+        self.pin_selection_top = !self.pin_selection_top;
+        if self.pin_selection_top && !self.filtered_url_indices.is_empty() {
+            self.list_state.select(Some(0));
+            self.reset_or_find_scroll();
+        }
+    }
+
+    fn toggle_follow_bottom(&mut self) {
+        self.follow_bottom = !self.follow_bottom;
+        if self.follow_bottom {
+            self.scroll_to_bottom();
+        }
+    }
+
+    fn scroll_content_up(&mut self, lines: u16) {
+        self.content_scroll = self.content_scroll.saturating_sub(lines);
+    }
+
+    // --- Links Navigator ---
+    // What the selected page calls one of its links, if --anchor-text recorded it
+    fn anchor_text(&self, link: &str) -> Option<&str> {
+        self.get_selected_url_str()
+            .and_then(|url| self.anchors.get(url))?
+            .iter()
+            .find(|(href, _)| href == link)
+            .map(|(_, text)| text.as_str())
+            .filter(|text| !text.is_empty())
+    }
+
+    fn selected_page_links(&self) -> &[String] {
+        self.get_selected_url_str()
+            .and_then(|url| self.page_links.get(url))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn show_links(&mut self) {
+        if self.selected_page_links().is_empty() {
+            self.status_message = Some("No links recorded for this page".to_string());
+            return;
+        }
+        self.links_state.select(Some(0));
+        self.popup = Some(Popup::Links);
+    }
+
+    fn select_next_link(&mut self) {
+        let len = self.selected_page_links().len();
+        if len > 0 {
+            let i = self.links_state.selected().map_or(0, |i| (i + 1) % len);
+            self.links_state.select(Some(i));
+        }
+    }
+
+    fn select_previous_link(&mut self) {
+        let len = self.selected_page_links().len();
+        if len > 0 {
+            let i = self
+                .links_state
+                .selected()
+                .map_or(0, |i| if i == 0 { len - 1 } else { i - 1 });
+            self.links_state.select(Some(i));
+        }
+    }
+
+    // One "link<TAB>scope<TAB>state" row per outbound link of the selected page, where scope
+    // is in-scope or out-of-scope and state is crawled, failed, queued or not-crawled. With
+    // --anchor-text each row ends with a fourth column, the link's text.
+    fn outbound_links_text(&self) -> String {
+        let Some(base_url) = &self.launch_config.seed_url else {
+            return String::new();
+        };
+        let queue = self.url_queue.try_lock().ok();
+        self.selected_page_links()
+            .iter()
+            .map(|link| {
+                let in_scope = Url::parse(link)
+                    .is_ok_and(|url| is_in_scope(&url, base_url, &self.launch_config));
+                let state = if self.crawl_error(link).is_some() {
+                    "failed"
+                } else if self.body_texts.contains_key(link) {
+                    "crawled"
+                } else if queue.as_ref().is_some_and(|q| queue_contains(q, link)) {
+                    "queued"
+                } else {
+                    "not-crawled"
+                };
+                let scope = if in_scope { "in-scope" } else { "out-of-scope" };
+                let shown = self.url_display.apply(link);
+                if self.launch_config.anchor_text {
+                    // Stored under the raw link, not its display form
+                    let text = self.anchor_text(link).unwrap_or_default();
+                    format!("{}\t{}\t{}\t{}\n", shown, scope, state, text)
+                } else {
+                    format!("{}\t{}\t{}\n", shown, scope, state)
+                }
+            })
+            .collect()
+    }
+
+    fn export_outbound_links(&mut self, to_clipboard: bool) {
+        let contents = self.outbound_links_text();
+        let count = self.selected_page_links().len();
+        let result = if to_clipboard {
+            copy_to_clipboard(&contents, self.clipboard)
+        } else {
+            write_export("crabcrawl-links.tsv", &contents, self.compress_exports)
+                .map(|path| path.display().to_string())
+                .map_err(|e| e.to_string())
+        };
+        self.status_message = Some(match result {
+            Ok(destination) if to_clipboard => {
+                format!("Copied {} links to {}", count, destination)
+            }
+            Ok(destination) => format!("Wrote {} links to {}", count, destination),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    // Jumps to the chosen link's result if it was crawled, otherwise queues it for crawling
+    fn follow_selected_link(&mut self) {
+        let Some(link) = self
+            .links_state
+            .selected()
+            .and_then(|i| self.selected_page_links().get(i))
+            .cloned()
+        else {
+            return;
+        };
+        self.popup = None;
+
+        if self.body_texts.contains_key(&link) {
+            if !self.select_url(&link) {
+                self.status_message = Some(format!("{} is hidden by the filters", link));
+            }
+            return;
+        }
+
+        // The crawler only holds the queue briefly, so a busy lock is rare and safe to report
+        let depth = self
+            .get_selected_url_str()
+            .and_then(|url| self.depths.get(url))
+            .map_or(0, |depth| depth + 1);
+        self.status_message = Some(match self.url_queue.try_lock() {
+            Ok(queue) if queue_contains(&queue, &link) => format!("{} is already queued", link),
+            Ok(mut queue) => {
+                queue.push_back(QueuedUrl {
+                    url: link.clone(),
+                    depth,
+                    parent: self.get_selected_url_str().map(str::to_string),
+                });
+                // No worker is left to pop it, so start one as refresh_all does
+                if self.crawl_finished_at.is_some() {
+                    self.crawl_started_at = Instant::now();
+                    self.crawl_finished_at = None;
+                    self.restart_crawler = true;
+                }
+                format!("Queued {} for crawling", link)
+            }
+            Err(_) => "Queue is busy, try again".to_string(),
+        });
+    }
+
+    // Selects the given URL in the list, clearing filters if they hide it
+    fn select_url(&mut self, url: &str) -> bool {
+        let Some(original_idx) = self.visited_urls.iter().position(|u| u == url) else {
+            return false;
+        };
+        if !self.filtered_url_indices.contains(&original_idx) {
+            self.min_body_chars = 0;
+            self.domain_filter = None;
+            self.clear_search();
+        }
+        match self
+            .filtered_url_indices
+            .iter()
+            .position(|&idx| idx == original_idx)
+        {
+            Some(filtered_pos) => {
+                self.list_state.select(Some(filtered_pos));
+                self.reset_or_find_scroll();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // --- Structured Data ---
+    fn show_structured_data(&mut self) {
+        self.popup_scroll = 0;
+        self.popup = Some(Popup::StructuredData);
+    }
+
+    fn selected_json_ld_text(&self) -> String {
+        match self
+            .get_selected_url_str()
+            .and_then(|url| self.json_ld.get(url))
+        {
+            Some(blocks) => blocks
+                .iter()
+                .map(|block| serde_json::to_string_pretty(block).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            None => {
+                "No JSON-LD found on this page (crawl with --jsonld to extract it).".to_string()
+            }
+        }
+    }
+
+    // Writes every page's structured data to a single JSON file keyed by URL
+    fn export_json_ld(&mut self) {
+        let export: serde_json::Map<String, serde_json::Value> = self
+            .visited_urls
+            .iter()
+            .filter_map(|url| {
+                let blocks = self.json_ld.get(url)?;
+                Some((
+                    self.url_display.apply(url).into_owned(),
+                    serde_json::Value::from(blocks.clone()),
+                ))
+            })
+            .collect();
+        let contents = serde_json::to_string_pretty(&export).unwrap_or_default();
+        self.status_message = Some(
+            match write_export("crabcrawl-jsonld.json", &contents, self.compress_exports) {
+                Ok(path) => format!(
+                    "Wrote JSON-LD for {} pages to {}",
+                    export.len(),
+                    path.display()
+                ),
+                Err(e) => format!("Export failed: {}", e),
+            },
+        );
+    }
+
+    // --- Queue Editor ---
+    // Every action only try_locks the queue: the crawler holds it for short, await-free
+    // sections, and blocking the UI thread on it could stall the whole event loop.
+    fn show_queue(&mut self) {
+        self.queue_state.select(Some(0));
+        self.refresh_queue_snapshot();
+        self.popup = Some(Popup::Queue);
+    }
+
+    // Keeps the last snapshot when the crawler has the queue locked
+    fn refresh_queue_snapshot(&mut self) {
+        if let Ok(queue) = self.url_queue.try_lock() {
+            self.queue_snapshot = queue.iter().map(|queued| queued.url.clone()).collect();
+        }
+        let len = self.queue_snapshot.len();
+        match self.queue_state.selected() {
+            _ if len == 0 => self.queue_state.select(None),
+            Some(i) if i >= len => self.queue_state.select(Some(len - 1)),
+            None => self.queue_state.select(Some(0)),
+            Some(_) => {}
+        }
+    }
+
+    fn select_next_queued(&mut self) {
+        let len = self.queue_snapshot.len();
+        if len > 0 {
+            let i = self.queue_state.selected().map_or(0, |i| (i + 1) % len);
+            self.queue_state.select(Some(i));
+        }
+    }
+
+    fn select_previous_queued(&mut self) {
+        let len = self.queue_snapshot.len();
+        if len > 0 {
+            let i = self
+                .queue_state
+                .selected()
+                .map_or(0, |i| if i == 0 { len - 1 } else { i - 1 });
+            self.queue_state.select(Some(i));
+        }
+    }
+
+    fn selected_queued_url(&self) -> Option<String> {
+        self.queue_state
+            .selected()
+            .and_then(|i| self.queue_snapshot.get(i))
+            .cloned()
+    }
+
+    // Entries are found by URL since the crawler may have popped some since the snapshot
+    fn remove_selected_queued(&mut self) {
+        let Some(url) = self.selected_queued_url() else {
+            return;
+        };
+        self.status_message = Some(match self.url_queue.try_lock() {
+            Ok(mut queue) => {
+                queue.retain(|queued| queued.url != url);
+                format!("Removed {} from the queue", url)
+            }
+            Err(_) => "Queue is busy, try again".to_string(),
+        });
+        self.refresh_queue_snapshot();
+    }
+
+    fn prioritize_selected_queued(&mut self) {
+        let Some(url) = self.selected_queued_url() else {
+            return;
+        };
+        self.status_message = Some(match self.url_queue.try_lock() {
+            Ok(mut queue) => match queue.iter().position(|queued| queued.url == url) {
+                Some(pos) => {
+                    let queued = queue.remove(pos).expect("position is in bounds");
+                    queue.push_front(queued);
+                    format!("{} will be crawled next", url)
+                }
+                None => format!("{} is no longer queued", url),
+            },
+            Err(_) => "Queue is busy, try again".to_string(),
+        });
+        self.queue_state.select(Some(0));
+        self.refresh_queue_snapshot();
+    }
+
+    fn clear_queue(&mut self) {
+        self.status_message = Some(match self.url_queue.try_lock() {
+            Ok(mut queue) => {
+                let removed = queue.len();
+                queue.clear();
+                format!("Cleared {} queued URLs", removed)
+            }
+            Err(_) => "Queue is busy, try again".to_string(),
+        });
+        self.refresh_queue_snapshot();
+    }
+
+    // --- Baseline ---
+    fn show_baseline(&mut self) {
+        if self.baseline.is_none() {
+            self.status_message =
+                Some("No baseline to compare with (start with --baseline <session>)".to_string());
+            return;
+        }
+        self.compare_with_baseline();
+        let listed = self.baseline_listed().len();
+        self.baseline_state.select((listed > 0).then_some(0));
+        self.popup = Some(Popup::Baseline);
+    }
+
+    // Pages are matched by URL and compared by body hash. A page that failed this time
+    // counts as removed, having no body to compare; so do pages the crawl hasn't reached.
+    fn compare_with_baseline(&mut self) {
+        let Some(baseline) = &self.baseline else {
+            return;
+        };
+        let mut diff = Vec::new();
+        let mut crawled = HashSet::new();
+        for (idx, url) in self.visited_urls.iter().enumerate() {
+            let Some(body) = self.body_texts.get(url) else {
+                continue;
+            };
+            if self.is_error_page(idx) {
+                continue;
+            }
+            crawled.insert(url.as_str());
+            let change = match baseline.body_hashes.get(url) {
+                None => BaselineChange::Added,
+                Some(&hash) if hash == body_hash(body) => BaselineChange::Unchanged,
+                Some(_) => BaselineChange::Changed,
+            };
+            diff.push((change, url.clone()));
+        }
+        let mut removed: Vec<&String> = baseline
+            .body_hashes
+            .keys()
+            .filter(|url| !crawled.contains(url.as_str()))
+            .collect();
+        removed.sort_unstable();
+        diff.extend(
+            removed
+                .into_iter()
+                .map(|url| (BaselineChange::Removed, url.clone())),
+        );
+        // Stable, so each kind keeps crawl order
+        diff.sort_by_key(|(change, _)| *change);
+        self.baseline_diff = diff;
+    }
+
+    fn baseline_count(&self, kind: BaselineChange) -> usize {
+        self.baseline_diff
+            .iter()
+            .filter(|(change, _)| *change == kind)
+            .count()
+    }
+
+    // The drill-down list leaves out unchanged pages, which sort last
+    fn baseline_listed(&self) -> &[(BaselineChange, String)] {
+        let unchanged = self.baseline_count(BaselineChange::Unchanged);
+        &self.baseline_diff[..self.baseline_diff.len() - unchanged]
+    }
+
+    fn select_next_baseline_entry(&mut self) {
+        let len = self.baseline_listed().len();
+        if len > 0 {
+            let i = self.baseline_state.selected().map_or(0, |i| (i + 1) % len);
+            self.baseline_state.select(Some(i));
+        }
+    }
+
+    fn select_previous_baseline_entry(&mut self) {
+        let len = self.baseline_listed().len();
+        if len > 0 {
+            let i = self
+                .baseline_state
+                .selected()
+                .map_or(0, |i| if i == 0 { len - 1 } else { i - 1 });
+            self.baseline_state.select(Some(i));
+        }
+    }
+
+    fn show_selected_baseline_entry(&mut self) {
+        let Some((change, url)) = self
+            .baseline_state
+            .selected()
+            .and_then(|i| self.baseline_listed().get(i))
+            .cloned()
+        else {
+            return;
+        };
+        if change == BaselineChange::Removed || !self.select_url(&url) {
+            self.status_message = Some(format!("{} wasn't crawled this time", url));
+            return;
+        }
+        self.popup = None;
+    }
+
+    // --- Harvest ---
+    fn show_harvest(&mut self) {
+        self.harvested = harvest_matches(
+            &self.harvest_pattern,
+            self.visited_urls
+                .iter()
+                .filter_map(|url| self.body_texts.get(url)),
+        );
+        self.popup_scroll = 0;
+        self.popup = Some(Popup::Harvest);
+    }
+
+    fn harvest_text(&self) -> String {
+        if self.harvested.is_empty() {
+            format!("No matches for {} yet.", self.harvest_pattern)
+        } else {
+            self.harvested.join("\n")
+        }
+    }
+
+    fn export_harvest(&mut self) {
+        let mut contents = self.harvested.join("\n");
+        contents.push('\n');
+        self.status_message = Some(
+            match write_export("crabcrawl-harvest.txt", &contents, self.compress_exports) {
+                Ok(path) => format!(
+                    "Wrote {} matches to {}",
+                    self.harvested.len(),
+                    path.display()
+                ),
+                Err(e) => format!("Export failed: {}", e),
+            },
+        );
+    }
+
+    // A plain-text picture of the list and the visible part of the content, for bug
+    // reports and notes
+    fn export_snapshot(&mut self) {
+        let selected = self.list_state.selected();
+        let query = if self.active_search_query.is_empty() {
+            "(none)".to_string()
+        } else {
+            format!(
+                "{} in {}",
+                self.active_search_query,
+                self.search_scope.label()
+            )
+        };
+        let mut contents = format!(
+            "crabcrawl snapshot of {}\nSearch: {}\nContent scroll: {}\n\n== URLs ({} of {}) ==\n",
+            self.seed_url,
+            query,
+            self.content_scroll,
+            self.filtered_url_indices.len(),
+            self.visited_urls.len()
+        );
+        for (i, url) in self.get_displayed_urls().into_iter().enumerate() {
+            let marker = if selected == Some(i) { ">> " } else { "   " };
+            let indent = "  ".repeat(self.tree_depths.get(i).copied().unwrap_or(0));
+            contents.push_str(&format!(
+                "{}{}{}\n",
+                marker,
+                indent,
+                self.format_list_item(i + 1, url)
+            ));
+        }
+
+        let url = self
+            .get_selected_url_str()
+            .map_or(Cow::Borrowed("<None Selected>"), |url| {
+                self.display_url(url)
+            });
+        contents.push_str(&format!("\n== Content: {} ==\n", url));
+        if let Some(content) = self.displayed_content() {
+            let visible = self.visible_lines();
+            let rows = self.content_area.height.saturating_sub(2) as usize;
+            let shown = content
+                .lines()
+                .enumerate()
+                .skip(self.line_at_row(self.content_scroll as usize))
+                .filter(|(idx, _)| visible.as_ref().is_none_or(|visible| visible[*idx]))
+                .take(rows);
+            for (_, line) in shown {
+                contents.push_str(line);
+                contents.push('\n');
+            }
+        }
+
+        self.status_message = Some(
+            match write_export("crabcrawl-snapshot.txt", &contents, self.compress_exports) {
+                Ok(path) => format!("Wrote a snapshot of the view to {}", path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            },
+        );
+    }
+
+    fn show_info(&mut self) {
+        self.popup = Some(Popup::Info);
+    }
+
+    fn show_full_url(&mut self) {
+        if self.get_selected_url_str().is_some() {
+            self.popup = Some(Popup::FullUrl);
+        }
+    }
+
+    // --- Redirects ---
+    // Pages stay keyed by the requested URL; this only picks which one is shown, and
+    // writes it in the --display-url form
+    fn display_url<'a>(&'a self, url: &'a str) -> Cow<'a, str> {
+        match self.final_urls.get(url) {
+            Some(final_url) if self.show_final_urls => self.url_display.apply(final_url),
+            _ => self.url_display.apply(url),
+        }
+    }
+
+    fn toggle_final_urls(&mut self) {
+        self.show_final_urls = !self.show_final_urls;
+        self.status_message = Some(
+            if self.show_final_urls {
+                "Showing final URLs after redirects"
+            } else {
+                "Showing requested URLs"
+            }
+            .to_string(),
+        );
+    }
+
+    fn toggle_time_display(&mut self) {
+        self.time_display = match self.time_display {
+            TimeDisplay::Relative => TimeDisplay::Absolute,
+            TimeDisplay::Absolute => TimeDisplay::Relative,
+        };
+        self.status_message = Some(
+            match self.time_display {
+                TimeDisplay::Relative => "Showing relative crawl times",
+                TimeDisplay::Absolute => "Showing absolute crawl times",
+            }
+            .to_string(),
+        );
+    }
+
+    // When the page was crawled, in the chosen style; empty if unknown
+    fn crawled_time(&self, url: &str) -> String {
+        self.crawled_at.get(url).map_or(String::new(), |&secs| {
+            format_timestamp(secs, self.time_display)
+        })
+    }
+
+    // Fills the --list-format template for one list row
+    fn format_list_item(&self, number: usize, url: &str) -> String {
+        let body = self.body_texts.get(url);
+        let status = match body {
+            None => "loading",
+            Some(body) if is_placeholder_body(body) => "error",
+            Some(_) if self.encoding_issues.contains_key(url) => "encoding",
+            Some(_) => "ok",
+        };
+        self.list_format
+            .replace("{n}", &number.to_string())
+            .replace("{url}", &self.display_url(url))
+            .replace("{title}", self.titles.get(url).map_or("", String::as_str))
+            .replace(
+                "{len}",
+                &body.map_or(0, |body| body.chars().count()).to_string(),
+            )
+            .replace("{size}", &format_bytes(body.map_or(0, String::len)))
+            .replace(
+                "{depth}",
+                &self
+                    .depths
+                    .get(url)
+                    .map_or(String::new(), |d| d.to_string()),
+            )
+            .replace("{status}", status)
+            .replace(
+                "{time}",
+                &self
+                    .load_times
+                    .get(url)
+                    .map_or(String::new(), |&ms| format_millis(ms)),
+            )
+            .replace("{crawled}", &self.crawled_time(url))
+    }
+
+    // --- Sitemap ---
+    // sitemap.xml with <lastmod> from crawl times, or a plain sitemap.txt for other extensions
+    fn sitemap_contents(&self, xml: bool) -> String {
+        let urls = self.visited_urls.iter().filter(|url| {
+            self.body_texts
+                .get(*url)
+                .is_some_and(|body| !is_placeholder_body(body))
+        });
+        if !xml {
+            return urls
+                .map(|url| format!("{}\n", self.url_display.apply(url)))
+                .collect();
+        }
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for url in urls {
+            out.push_str(&format!(
+                "  <url>\n    <loc>{}</loc>\n",
+                xml_escape(&self.url_display.apply(url))
+            ));
+            if let Some(&secs) = self.crawled_at.get(url) {
+                out.push_str(&format!("    <lastmod>{}</lastmod>\n", format_date(secs)));
+            }
+            out.push_str("  </url>\n");
+        }
+        out.push_str("</urlset>\n");
+        out
+    }
+
+    // To --sitemap-out if given, otherwise sitemap.xml in the current directory
+    fn write_sitemap(&mut self) {
+        let path = self
+            .sitemap_out
+            .clone()
+            .unwrap_or_else(|| "sitemap.xml".into());
+        // sitemap.xml.gz is still a sitemap.xml
+        let xml = path
+            .to_string_lossy()
+            .trim_end_matches(".gz")
+            .ends_with(".xml");
+        let contents = self.sitemap_contents(xml);
+        let result = write_export(&path.to_string_lossy(), &contents, self.compress_exports);
+        self.status_message = Some(match result {
+            Ok(path) => format!("Wrote sitemap to {}", path.display()),
+            Err(e) => format!("Sitemap export failed: {}", e),
+        });
+    }
+
+    // --- Sessions ---
+    fn to_session(&self, seed_url: &Url) -> Session {
+        let pages = self
+            .visited_urls
+            .iter()
+            .filter_map(|url| {
+                Some(CrawledPage {
+                    url: url.clone(),
+                    title: self.titles.get(url).cloned().unwrap_or_default(),
+                    crawled_at: self.crawled_at.get(url).copied().unwrap_or(0),
+                    load_ms: self.load_times.get(url).copied().unwrap_or(0),
+                    final_url: self.final_urls.get(url).cloned(),
+                    content_selector: self.content_sources.get(url).cloned(),
+                    parent: self.parents.get(url).cloned(),
+                    depth: self.depths.get(url).copied().unwrap_or(0),
+                    // Failed pages are saved with their errors instead
+                    body: self
+                        .body_texts
+                        .get(url)
+                        .filter(|body| *body != BODY_LOAD_FAILED)?
+                        .clone(),
+                    links: self.page_links.get(url).cloned().unwrap_or_default(),
+                    anchors: self.anchors.get(url).cloned().unwrap_or_default(),
+                    json_ld: self.json_ld.get(url).cloned().unwrap_or_default(),
+                    html: self.html_sources.get(url).cloned(),
+                })
+            })
+            .collect();
+        Session {
+            seed_url: seed_url.to_string(),
+            saved_at: unix_now(),
+            pages,
+            failed: self
+                .errors
+                .iter()
+                .map(|(&idx, error)| (self.visited_urls[idx].clone(), error.clone()))
+                .collect(),
+        }
+    }
+
+    // A restored session is browsed as a finished crawl
+    fn restore_session(&mut self, session: Session) {
+        for page in session.pages {
+            self.add_page(page);
+        }
+        for (url, error) in session.failed {
+            self.add_crawl_failure(url, error);
+        }
+        self.crawl_finished_at = Some(self.crawl_started_at);
+        self.awaiting_first_result = true; // Pages crawled from here on are the new crawl's
+        self.status_message = Some(format!("Resumed session of {}", session.seed_url));
+    }
+
+    // --- Crawl Control ---
+    fn toggle_link_expansion(&mut self) {
+        let expanding = self.crawl_control.is_expanding();
+        self.crawl_control.set_expanding(!expanding);
+    }
+
+    // Workers finish the page they're on before the pause takes hold
+    fn toggle_pause(&mut self) {
+        let paused = self.crawl_control.is_paused();
+        self.crawl_control.set_paused(!paused);
+        self.status_message = Some(if paused {
+            "Crawl resumed".to_string()
+        } else {
+            "Crawl paused, Space resumes".to_string()
+        });
+    }
+
+    // The crawl's config with settings changed at runtime folded in
+    fn effective_config(&self) -> Config {
+        Config {
+            min_body_chars: self.min_body_chars,
+            ..self.launch_config.clone()
+        }
+    }
+
+    // The crawl as a command line
+    fn copy_command_line(&mut self) {
+        let command = std::iter::once("crabcrawl".to_string())
+            .chain(
+                self.effective_config()
+                    .to_cli_args()
+                    .iter()
+                    .map(|arg| shell_quote(arg)),
+            )
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.status_message = Some(match copy_to_clipboard(&command, self.clipboard) {
+            Ok(destination) => format!("Copied to {}: {}", destination, command),
+            Err(e) => format!("Copy failed: {}", e),
+        });
+    }
+
+    // Re-crawls every listed page; old bodies stay visible until the new ones replace them
+    fn refresh_all(&mut self) {
+        let (Ok(mut queue), Ok(mut visited)) = (self.url_queue.try_lock(), self.visited.try_lock())
+        else {
+            self.status_message = Some("Queue is busy, try again".to_string());
+            return;
+        };
+        let urls: Vec<String> = self
+            .visited_urls
+            .iter()
+            .filter(|url| self.body_texts.contains_key(*url))
+            .cloned()
+            .collect();
+        for url in &urls {
+            visited.remove(url);
+            if !queue_contains(&queue, url) {
+                queue.push_back(QueuedUrl {
+                    url: url.clone(),
+                    depth: self.depths.get(url).copied().unwrap_or(0),
+                    parent: self.parents.get(url).cloned(),
+                });
+            }
+        }
+        drop(queue);
+        drop(visited);
+
+        self.refresh_total = urls.len();
+        self.refresh_pending = urls.into_iter().collect();
+        if self.crawl_finished_at.is_some() {
+            self.crawl_started_at = Instant::now();
+            self.crawl_finished_at = None;
+            self.restart_crawler = true;
+        }
+        self.status_message = Some(format!("Refreshing {} pages", self.refresh_total));
+    }
+
+    // Queues every in-scope link seen on a crawled page that was never crawled itself, and
+    // restarts the crawler if it had exited: a crawl cut short by its time limit, a full
+    // domain or a lost WebDriver session (or a resumed session) picks up its frontier
+    fn requeue_frontier(&mut self) {
+        let Some(base_url) = &self.launch_config.seed_url else {
+            return;
+        };
+        let (Ok(mut queue), Ok(visited)) = (self.url_queue.try_lock(), self.visited.try_lock())
+        else {
+            self.status_message = Some("Queue is busy, try again".to_string());
+            return;
+        };
+        let mut added = 0;
+        for page_url in &self.visited_urls {
+            let Some(links) = self.page_links.get(page_url) else {
+                continue;
+            };
+            let depth = self.depths.get(page_url).copied().unwrap_or(0) + 1;
+            for link in links {
+                let in_scope = Url::parse(link)
+                    .is_ok_and(|url| is_in_scope(&url, base_url, &self.launch_config));
+                if in_scope
+                    && !visited.contains(link)
+                    && !self.body_texts.contains_key(link)
+                    && !queue_contains(&queue, link)
+                {
+                    queue.push_back(QueuedUrl {
+                        url: link.clone(),
+                        depth,
+                        parent: Some(page_url.clone()),
+                    });
+                    added += 1;
+                }
+            }
+        }
+        let queued = queue.len();
+        drop(queue);
+        drop(visited);
+
+        if added == 0 {
+            self.status_message = Some("No uncrawled links left to queue".to_string());
+            return;
+        }
+        if self.crawl_finished_at.is_some() {
+            self.crawl_started_at = Instant::now();
+            self.crawl_finished_at = None;
+            self.restart_crawler = true;
+            self.awaiting_first_result = true;
+        }
+        self.status_message = Some(format!(
+            "Queued {} uncrawled links, {} waiting",
+            added, queued
+        ));
+    }
+
+    // (done, total) while a refresh is in flight
+    fn refresh_progress(&self) -> Option<(usize, usize)> {
+        (!self.refresh_pending.is_empty()).then(|| {
+            (
+                self.refresh_total - self.refresh_pending.len(),
+                self.refresh_total,
+            )
+        })
+    }
+}
+
+// Tabs become spaces up to the next tab stop, then the indentation shared by every
+// non-blank line is removed
+fn clean_up_indentation(content: &str) -> String {
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let mut expanded = String::with_capacity(line.len());
+            let mut column = 0;
+            for c in line.chars() {
+                if c == '\t' {
+                    let spaces = CLEAN_VIEW_TAB_WIDTH - column % CLEAN_VIEW_TAB_WIDTH;
+                    expanded.extend(std::iter::repeat_n(' ', spaces));
+                    column += spaces;
+                } else {
+                    expanded.push(c);
+                    column += 1;
+                }
+            }
+            expanded
+        })
+        .collect();
+    let common_indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(common_indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Start offsets of the query in already lowercased text. With whole_word a match must not
+// have a letter, digit or underscore right before or after it, so `cat` skips `category`.
+fn query_matches<'a>(
+    text_lower: &'a str,
+    query_lower: &'a str,
+    whole_word: bool,
+) -> impl Iterator<Item = usize> + 'a {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    text_lower
+        .match_indices(query_lower)
+        .map(|(start, _)| start)
+        .filter(move |&start| {
+            let end = start + query_lower.len();
+            !whole_word
+                || (!text_lower[..start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(is_word_char)
+                    && !text_lower[end..].chars().next().is_some_and(is_word_char))
+        })
+}
+
+// The query as the terms to look for: the whole thing as a phrase, or each of its words
+fn search_terms(query_lower: &str, mode: TermMode) -> Vec<&str> {
+    match mode {
+        TermMode::Phrase => vec![query_lower],
+        TermMode::Any | TermMode::All => query_lower.split_whitespace().collect(),
+    }
+}
+
+// Byte ranges of every term in already lowercased text, in order, with overlaps merged
+fn term_matches(text_lower: &str, terms: &[&str], whole_word: bool) -> Vec<(usize, usize)> {
+    let mut found: Vec<(usize, usize)> = terms
+        .iter()
+        .flat_map(|term| {
+            query_matches(text_lower, term, whole_word)
+                .map(move |start| (start, start + term.len()))
+        })
+        .collect();
+    found.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(found.len());
+    for (start, end) in found {
+        match merged.last_mut() {
+            Some(last) if start < last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+// The first max_chars of a line, plus how many characters were cut off
+fn cap_line(line: &str, max_chars: usize) -> (&str, usize) {
+    match line.char_indices().nth(max_chars) {
+        Some((end, _)) => (&line[..end], line[end..].chars().count()),
+        None => (line, 0),
+    }
+}
+
+// Drawn after the first line of a run of identical lines
+fn repeat_marker(count: usize) -> String {
+    format!(" (×{count})")
+}
+
+// Whether the line before `idx` was hidden by the grep view rather than folded into a run
+fn grep_gap_before(visible: &[bool], repeats: Option<&[usize]>, idx: usize) -> bool {
+    idx > 0 && !visible[idx - 1] && repeats.is_none_or(|repeats| repeats[idx - 1] > 0)
+}
+
+// Rows a line takes when word-wrapped to `width` columns, breaking at spaces the way the
+// content Paragraph does and splitting words that are wider than a whole row.
+fn wrapped_row_count(line: &str, width: usize) -> usize {
+    if width == 0 {
+        return 1;
+    }
+    let mut rows = 1;
+    let mut row_width = 0;
+    for word in line.split_inclusive(' ') {
+        let word_width = word.trim_end_matches(' ').width();
+        if row_width > 0 && row_width + word_width > width {
+            rows += 1;
+            row_width = 0;
+        }
+        if word_width > width {
+            rows += (word_width - 1) / width;
+            row_width = word_width % width;
+        } else {
+            row_width += word.width();
+        }
+    }
+    rows
+}
+
+// Every distinct match of `pattern` in `bodies`, in order of first appearance
+fn harvest_matches<'a>(pattern: &Regex, bodies: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut matches = Vec::new();
+    for body in bodies {
+        for found in pattern.find_iter(body) {
+            if seen.insert(found.as_str()) {
+                matches.push(found.as_str().to_string());
+            }
+        }
+    }
+    matches
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// YYYY-MM-DD (UTC) for Unix seconds, using the days-from-civil algorithm in reverse
+fn format_date(secs: u64) -> String {
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// YYYY-MM-DDTHH:MM:SSZ for Unix seconds
+fn format_iso8601(secs: u64) -> String {
+    let time_of_day = secs % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(secs),
+        time_of_day / 3600,
+        (time_of_day % 3600) / 60,
+        time_of_day % 60
+    )
+}
+
+// A crawl time as "just now", "4m ago", "3h ago" or "2d ago", or in full
+fn format_timestamp(secs: u64, display: TimeDisplay) -> String {
+    if display == TimeDisplay::Absolute {
+        return format_iso8601(secs);
+    }
+    let age = unix_now().saturating_sub(secs);
+    match age {
+        0..10 => "just now".to_string(),
+        10..60 => format!("{}s ago", age),
+        60..3600 => format!("{}m ago", age / 60),
+        3600..86_400 => format!("{}h ago", age / 3600),
+        _ => format!("{}d ago", age / 86_400),
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// --- Result Stream ---
+// --output-fifo / --output-fd: every page and failure as one line of JSON, written as it
+// arrives so another process can consume the crawl live
+struct ResultStream {
+    file: std::fs::File,
+}
+
+impl ResultStream {
+    // Opening a named pipe blocks until something opens it for reading
+    fn open(path: &std::path::Path) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)?;
+        Ok(ResultStream { file })
+    }
+
+    // Fails once the reader has gone away (a broken pipe)
+    fn write(&mut self, message: &CrawlerMessage) -> io::Result<()> {
+        let record = match message {
+            CrawlerMessage::Page(page) => serde_json::to_value(page)?,
+            CrawlerMessage::Failed { url, error } => {
+                serde_json::json!({ "url": url, "error": error.message, "kind": error.kind })
+            }
+            CrawlerMessage::Fetching(_)
+            | CrawlerMessage::Discarded(_)
+            | CrawlerMessage::Status(_) => return Ok(()),
+        };
+        writeln!(self.file, "{}", record)?;
+        self.file.flush()
+    }
+}
+
+// --- Metrics ---
+// --metrics-port: crawl counters for Prometheus, served over HTTP by a task of their own.
+// Like the result stream they outlive each crawl, so the totals only ever go up.
+struct Metrics {
+    pages: AtomicUsize,
+    errors: AtomicUsize,
+    bytes: AtomicUsize,
+    queue_size: AtomicUsize,      // Of the current crawl
+    crawl_duration_ms: AtomicU64, // Of the current crawl
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Metrics {
+            pages: AtomicUsize::new(0),
+            errors: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+            queue_size: AtomicUsize::new(0),
+            crawl_duration_ms: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, message: &CrawlerMessage) {
+        match message {
+            CrawlerMessage::Page(page) => {
+                self.pages.fetch_add(1, Ordering::Relaxed);
+                self.bytes.fetch_add(page.body.len(), Ordering::Relaxed);
+            }
+            CrawlerMessage::Failed { .. } => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+            CrawlerMessage::Fetching(_)
+            | CrawlerMessage::Discarded(_)
+            | CrawlerMessage::Status(_) => {}
+        }
+    }
+
+    fn set_progress(&self, queue_size: usize, elapsed: Duration) {
+        self.queue_size.store(queue_size, Ordering::Relaxed);
+        self.crawl_duration_ms
+            .store(elapsed.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    // The text exposition format, https://prometheus.io/docs/instrumenting/exposition_formats/
+    fn render(&self) -> String {
+        let duration = self.crawl_duration_ms.load(Ordering::Relaxed) as f64 / 1000.0;
+        let metrics: [(&str, &str, &str, String); 5] = [
+            (
+                "pages_total",
+                "counter",
+                "Pages crawled, re-crawls included",
+                counter_value(&self.pages),
+            ),
+            (
+                "errors_total",
+                "counter",
+                "Pages that failed to crawl",
+                counter_value(&self.errors),
+            ),
+            (
+                "bytes_total",
+                "counter",
+                "Body text crawled, in bytes",
+                counter_value(&self.bytes),
+            ),
+            (
+                "queue_size",
+                "gauge",
+                "URLs waiting in the current crawl's queue",
+                counter_value(&self.queue_size),
+            ),
+            (
+                "crawl_duration_seconds",
+                "gauge",
+                "How long the current crawl has run",
+                duration.to_string(),
+            ),
+        ];
+        let mut text = String::new();
+        for (name, kind, help, value) in metrics {
+            text.push_str(&format!(
+                "# HELP crabcrawl_{name} {help}\n# TYPE crabcrawl_{name} {kind}\ncrabcrawl_{name} {value}\n"
+            ));
+        }
+        text
+    }
+}
+
+fn counter_value(counter: &AtomicUsize) -> String {
+    counter.load(Ordering::Relaxed).to_string()
+}
+
+// Answers GET /metrics on every connection; anything else gets a 404
+async fn serve_metrics(listener: tokio::net::TcpListener, metrics: Arc<Metrics>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    loop {
+        let Ok((mut socket, _)) = listener.accept().await else {
+            // Errors like running out of file descriptors repeat at once; don't spin on them
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            continue;
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let Ok(read) = socket.read(&mut request).await else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&request[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = if path == "/metrics" {
+                ("200 OK", metrics.render())
+            } else {
+                ("404 Not Found", "Not found, try /metrics\n".to_string())
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.ok();
+        });
+    }
+}
+
+// --- Sessions ---
+// Every crawl is saved to the cache directory when its view is closed, so it can be
+// browsed again later with --resume <file> or --continue-last.
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    seed_url: String,
+    saved_at: u64, // Seconds since the Unix epoch
+    pages: Vec<CrawledPage>,
+    failed: Vec<(String, CrawlError)>,
+}
+
+// $XDG_CACHE_HOME/crabcrawl/sessions, falling back to ~/.cache
+fn sessions_dir() -> Option<std::path::PathBuf> {
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".cache"))
+        })?;
+    Some(cache.join("crabcrawl").join("sessions"))
+}
+
+fn save_session(session: &Session) -> io::Result<std::path::PathBuf> {
+    let dir = sessions_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let contents = serde_json::to_string(session).map_err(io::Error::other)?;
+    // Two crawls can end within the same second, so a counter keeps the later one from
+    // overwriting the earlier
+    for n in 0..1000 {
+        let path = dir.join(format!("session-{}-{:03}.json", session.saved_at, n));
+        let mut file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        file.write_all(contents.as_bytes())?;
+        return Ok(path);
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "too many sessions saved this second",
+    ))
+}
+
+fn load_session(path: &std::path::Path) -> Result<Session, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("can't read session {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not a crabcrawl session: {}", path.display(), e))
+}
+
+// What --baseline keeps of a saved session: enough to tell whether a page changed
+#[derive(Clone)]
+struct Baseline {
+    seed_url: String,
+    saved_at: u64,
+    body_hashes: HashMap<String, u64>, // By URL
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BaselineChange {
+    Changed,
+    Added,
+    Removed,
+    Unchanged,
+}
+
+impl BaselineChange {
+    fn label(self) -> &'static str {
+        match self {
+            BaselineChange::Changed => "changed",
+            BaselineChange::Added => "added",
+            BaselineChange::Removed => "removed",
+            BaselineChange::Unchanged => "unchanged",
+        }
+    }
+}
+
+fn load_baseline(path: &std::path::Path) -> Result<Baseline, String> {
+    let session = load_session(path)?;
+    let body_hashes = session
+        .pages
+        .iter()
+        .filter(|page| !is_placeholder_body(&page.body))
+        .map(|page| (page.url.clone(), body_hash(&page.body)))
+        .collect();
+    Ok(Baseline {
+        seed_url: session.seed_url,
+        saved_at: session.saved_at,
+        body_hashes,
+    })
+}
+
+// The fixed-width timestamps and counters in the file names make the newest one sort last
+fn latest_session_path() -> Option<std::path::PathBuf> {
+    std::fs::read_dir(sessions_dir()?)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("session-") && name.ends_with(".json"))
+        })
+        .max()
+}
+
+// --- Profiles ---
+// Named sets of options in $XDG_CONFIG_HOME/crabcrawl/profiles, one argument per line, so
+// --profile docs-site brings back a site's seed, selectors and limits. Blank lines and
+// lines starting with # are skipped, so a profile can be edited by hand.
+
+fn profiles_dir() -> Option<std::path::PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+        })?;
+    Some(config.join("crabcrawl").join("profiles"))
+}
+
+// Letters, digits, '-', '_' and '.', so a name can't climb out of the profiles directory
+fn is_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+fn profile_path(name: &str) -> Result<std::path::PathBuf, String> {
+    if !is_profile_name(name) {
+        return Err(format!(
+            "'{}' isn't a profile name (use letters, digits, '-', '_' and '.')",
+            name
+        ));
+    }
+    let dir = profiles_dir().ok_or("no config directory")?;
+    Ok(dir.join(format!("{}.args", name)))
+}
+
+fn save_profile(name: &str, config: &Config) -> Result<std::path::PathBuf, String> {
+    let path = profile_path(name)?;
+    let mut contents = format!("# crabcrawl profile {}\n", name);
+    for arg in config.to_cli_args() {
+        contents.push_str(&arg);
+        contents.push('\n');
+    }
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, contents));
+    written.map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn load_profile(name: &str) -> Result<Vec<String>, String> {
+    let path = profile_path(name)?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("can't read profile '{}' ({}): {}", name, path.display(), e))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+// Replaces each --profile <name> with the arguments saved in it, so whatever follows on
+// the command line overrides the profile
+fn expand_profiles(args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut expanded = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg != "--profile" {
+            expanded.push(arg);
+            continue;
+        }
+        let name = next_value(&mut args, &arg)?;
+        let profile = load_profile(&name)?;
+        if profile.iter().any(|arg| arg == "--profile") {
+            return Err(format!("profile '{}' can't load another profile", name));
+        }
+        expanded.extend(profile);
+    }
+    Ok(expanded)
+}
+
+// --- Exports ---
+
+// Writes an export into the current directory and returns where it ended up. With
+// --compress, or a name already ending in .gz, the file is gzipped.
+fn write_export(file_name: &str, contents: &str, compress: bool) -> io::Result<std::path::PathBuf> {
+    let mut path = std::env::current_dir()?.join(file_name);
+    if !compress && !file_name.ends_with(".gz") {
+        std::fs::write(&path, contents)?;
+        return Ok(path);
+    }
+    if !file_name.ends_with(".gz") {
+        path.as_mut_os_string().push(".gz");
+    }
+    let file = std::fs::File::create(&path)?;
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    encoder.write_all(contents.as_bytes())?;
+    encoder.finish()?;
+    Ok(path)
+}
+
+// --- Clipboard ---
+
+// Where copied text goes, for --clipboard
+#[derive(Clone, Copy, PartialEq)]
+enum ClipboardMethod {
+    Auto,  // A clipboard tool, or OSC 52 over SSH without a display; a file if both fail
+    Tool,  // pbcopy, wl-copy, xclip or xsel
+    Osc52, // An escape sequence asking the terminal emulator to set its own clipboard
+    File,  // A file in the temp directory, whose path is reported
+}
+
+impl ClipboardMethod {
+    fn parse(value: &str, flag: &str) -> Result<Self, String> {
+        match value {
+            "auto" => Ok(ClipboardMethod::Auto),
+            "tool" => Ok(ClipboardMethod::Tool),
+            "osc52" => Ok(ClipboardMethod::Osc52),
+            "file" => Ok(ClipboardMethod::File),
+            _ => Err(format!(
+                "'{}' expects auto, tool, osc52 or file, got '{}'",
+                flag, value
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ClipboardMethod::Auto => "auto",
+            ClipboardMethod::Tool => "tool",
+            ClipboardMethod::Osc52 => "osc52",
+            ClipboardMethod::File => "file",
+        }
+    }
+}
+
+// Copies text the way --clipboard asks and says where it went, for the status bar
+fn copy_to_clipboard(text: &str, method: ClipboardMethod) -> Result<String, String> {
+    match method {
+        ClipboardMethod::Tool => copy_with_tool(text).map(|()| "the clipboard".to_string()),
+        ClipboardMethod::Osc52 => copy_with_osc52(text),
+        ClipboardMethod::File => copy_to_file(text),
+        // Over plain SSH the tools either aren't there or can't reach a display
+        ClipboardMethod::Auto if is_remote_without_display() => copy_with_osc52(text),
+        ClipboardMethod::Auto => match copy_with_tool(text) {
+            Ok(()) => Ok("the clipboard".to_string()),
+            Err(tool_error) => {
+                copy_to_file(text).map_err(|file_error| format!("{}; {}", tool_error, file_error))
+            }
+        },
+    }
+}
+
+fn is_remote_without_display() -> bool {
+    let remote =
+        std::env::var_os("SSH_CONNECTION").is_some() || std::env::var_os("SSH_TTY").is_some();
+    remote && std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+// OSC 52 can't report back, so this only fails if the terminal can't be written to.
+// tmux passes it on with `set -g set-clipboard on`.
+fn copy_with_osc52(text: &str) -> Result<String, String> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+        .and_then(|()| stdout.flush())
+        .map_err(|e| format!("OSC 52: {}", e))?;
+    Ok("the terminal's clipboard".to_string())
+}
+
+fn copy_to_file(text: &str) -> Result<String, String> {
+    let path = std::env::temp_dir().join("crabcrawl-clipboard.txt");
+    std::fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path.display().to_string())
+}
+
+// Standard base64 with padding, all OSC 52 needs
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Hands text to the platform clipboard tool, trying each known one in turn
+fn copy_with_tool(text: &str) -> Result<(), String> {
+    const TOOLS: [(&str, &[&str]); 4] = [
+        ("pbcopy", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+    for (program, args) in TOOLS {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue; // Tool not installed, try the next one
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("{}: {}", program, e))?;
+        }
+        return match child.wait() {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(format!("{} exited with {}", program, status)),
+            Err(e) => Err(format!("{}: {}", program, e)),
+        };
+    }
+    Err("no clipboard tool found (pbcopy, wl-copy, xclip or xsel)".to_string())
+}
+
+// --- Crawl Control ---
+
+// Flags shared between the UI and the crawler task so a running crawl can be steered.
+struct CrawlControl {
+    // When false the crawler keeps draining `url_queue` but stops enqueueing newly found links.
+    expand_links: AtomicBool,
+    // Navigations counted against --max-requests, by every worker and every re-crawl
+    requests: AtomicUsize,
+    // While true workers finish their current page and take no new ones
+    paused: AtomicBool,
+    pages_crawled: AtomicUsize, // Loaded pages, for --pause-every
+}
+
+impl CrawlControl {
+    fn new() -> Self {
+        CrawlControl {
+            expand_links: AtomicBool::new(true),
+            requests: AtomicUsize::new(0),
+            paused: AtomicBool::new(false),
+            pages_crawled: AtomicUsize::new(0),
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    // Counts a loaded page, pausing the crawl when it completes a --pause-every batch
+    fn count_page(&self, pause_every: Option<usize>) {
+        let crawled = self.pages_crawled.fetch_add(1, Ordering::SeqCst) + 1;
+        if pause_every.is_some_and(|batch| crawled.is_multiple_of(batch)) {
+            self.set_paused(true);
+        }
+    }
+
+    fn pages_crawled(&self) -> usize {
+        self.pages_crawled.load(Ordering::Relaxed)
+    }
+
+    // Claims one navigation from the budget; false once it's spent
+    fn take_request(&self, budget: usize) -> bool {
+        self.requests
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |made| {
+                (made < budget).then_some(made + 1)
+            })
+            .is_ok()
+    }
+
+    fn requests_made(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    fn is_expanding(&self) -> bool {
+        self.expand_links.load(Ordering::Relaxed)
+    }
+
+    fn set_expanding(&self, expanding: bool) {
+        self.expand_links.store(expanding, Ordering::Relaxed);
+    }
+}
+
+// --- Crawler Task ---
+
+// Stand-in bodies stored when a page loaded but its text couldn't be read
+const BODY_EXTRACTION_FAILED: &str = "<Body text extraction failed>";
+const BODY_NOT_FOUND: &str = "<Body element not found>";
+// Listed for a page that couldn't be crawled at all; the reason is in AppState::errors
+const BODY_LOAD_FAILED: &str = "<Page failed to load>";
+const EMPTY_BODY_RETRY_DELAY: Duration = Duration::from_secs(1); // --on-empty-body retry
+
+fn is_placeholder_body(body: &str) -> bool {
+    body == BODY_EXTRACTION_FAILED || body == BODY_NOT_FOUND || body == BODY_LOAD_FAILED
+}
+
+// A URL waiting to be crawled
+struct QueuedUrl {
+    url: String,
+    depth: usize,           // Link hops from the seed URL
+    parent: Option<String>, // The page it was found on; None for the seed
+}
+
+type UrlQueue = Arc<Mutex<VecDeque<QueuedUrl>>>;
+type VisitedSet = Arc<Mutex<HashSet<String>>>; // URLs the crawler has already attempted
+
+// --- Crawler Workers ---
+// Any number of crawler_tasks can work through the same queue, each with its own WebDriver
+// session. They share one channel back to the UI and can be added or stopped mid-crawl.
+
+#[derive(Clone, Copy)]
+enum WorkerRequest {
+    Add,
+    Stop, // After the worker's current page
+}
+
+// State shared by every worker of one crawl
+struct WorkerContext {
+    base_url: Url,
+    url_queue: UrlQueue,
+    visited: VisitedSet,
+    control: Arc<CrawlControl>,
+    config: Arc<Config>,
+    sessions: Arc<SessionPool>,
+    pages_per_domain: Mutex<HashMap<String, usize>>, // Successfully loaded pages
+    // When the crawl started, for --max-duration-secs; workers added later share it. The
+    // duration budget includes connecting to WebDriver.
+    started_at: Instant,
+    // Workers between taking a URL and queueing its links. An idle worker only exits once
+    // this is zero, since a busy one may still refill the queue.
+    busy_workers: AtomicUsize,
+}
+
+// Marks its worker busy until dropped at the end of the page
+struct BusyWorker<'a>(&'a AtomicUsize);
+
+impl Drop for BusyWorker<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// The next URL to crawl, waiting out an empty queue while other workers are busy.
+// None once the crawl is done or this worker was asked to stop.
+async fn next_queued_url<'a>(
+    context: &'a WorkerContext,
+    stop: &AtomicBool,
+) -> Option<(QueuedUrl, BusyWorker<'a>)> {
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        {
+            // Checked under the queue lock, which busy workers hold while adding links.
+            // A paused crawl keeps its workers waiting on the queue until it resumes.
+            let mut queue = context.url_queue.lock().await;
+            // Marked visited as it's taken, so a page linking to it while it loads can't
+            // queue it again for another worker
+            while !context.control.is_paused()
+                && let Some(queued) = queue.pop_front()
+            {
+                if !context.visited.lock().await.insert(queued.url.clone()) {
+                    continue;
+                }
+                context.busy_workers.fetch_add(1, Ordering::SeqCst);
+                return Some((queued, BusyWorker(&context.busy_workers)));
+            }
+            if queue.is_empty() && context.busy_workers.load(Ordering::SeqCst) == 0 {
+                return None;
+            }
+        }
+        tokio::time::sleep(SELECTOR_POLL_INTERVAL).await;
+    }
+}
+
+struct CrawlerWorker {
+    handle: tokio::task::JoinHandle<()>,
+    stop: Arc<AtomicBool>, // Finish the current page, then exit
+}
+
+struct CrawlerPool {
+    context: Arc<WorkerContext>,
+    tx: mpsc::Sender<CrawlerMessage>,
+    rx: mpsc::Receiver<CrawlerMessage>,
+    workers: Vec<CrawlerWorker>,
+}
+
+impl CrawlerPool {
+    // Starts --sessions workers on whatever is in the queue
+    fn new(
+        base_url: &Url,
+        url_queue: UrlQueue,
+        visited: VisitedSet,
+        control: Arc<CrawlControl>,
+        config: Arc<Config>,
+        sessions: Arc<SessionPool>,
+    ) -> Self {
+        let worker_count = config.sessions;
+        let (tx, rx) = mpsc::channel::<CrawlerMessage>(CRAWLER_CHANNEL_BUFFER);
+        let context = Arc::new(WorkerContext {
+            base_url: base_url.clone(),
+            url_queue,
+            visited,
+            control,
+            config,
+            sessions,
+            pages_per_domain: Mutex::new(HashMap::new()),
+            started_at: Instant::now(),
+            busy_workers: AtomicUsize::new(0),
+        });
+        let mut pool = CrawlerPool {
+            context,
+            tx,
+            rx,
+            workers: Vec::new(),
+        };
+        for _ in 0..worker_count {
+            pool.add_worker();
+        }
+        pool
+    }
+
+    fn add_worker(&mut self) {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = tokio::spawn(crawler_task(
+            self.context.clone(),
+            self.tx.clone(),
+            stop.clone(),
+        ));
+        self.workers.push(CrawlerWorker { handle, stop });
+    }
+
+    // Asks the newest running worker to stop; the last one is kept
+    fn stop_worker(&mut self) -> bool {
+        if self.active_workers() <= 1 {
+            return false;
+        }
+        match self
+            .workers
+            .iter()
+            .rev()
+            .find(|worker| !worker.handle.is_finished() && !worker.stop.load(Ordering::Relaxed))
+        {
+            Some(worker) => {
+                worker.stop.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Running workers that haven't been asked to stop
+    fn active_workers(&self) -> usize {
+        self.workers
+            .iter()
+            .filter(|worker| !worker.handle.is_finished() && !worker.stop.load(Ordering::Relaxed))
+            .count()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.workers
+            .iter()
+            .all(|worker| worker.handle.is_finished())
+    }
+
+    fn abort(&self) {
+        for worker in &self.workers {
+            worker.handle.abort();
+        }
+    }
+
+    // Lets the workers run on their own; the channel closes once the last one exits
+    fn into_receiver(self) -> mpsc::Receiver<CrawlerMessage> {
+        self.rx
+    }
+}
+
+// --- WebDriver Sessions ---
+// Sessions outlive the workers using them: a worker that stops or runs out of work hands its
+// session back, and the next worker (or the next refresh's) takes it instead of opening a
+// new one. The idle ones are closed together when the crawl view is left.
+struct SessionPool {
+    idle: Mutex<Vec<Client>>,
+    opened: AtomicUsize, // Sessions connected so far, for rotating --user-agent-file
+}
+
+impl SessionPool {
+    fn new() -> Self {
+        SessionPool {
+            idle: Mutex::new(Vec::new()),
+            opened: AtomicUsize::new(0),
+        }
+    }
+
+    // A new session, with the next User-Agent in the rotation
+    async fn connect(
+        &self,
+        config: &Config,
+        tx: &mpsc::Sender<CrawlerMessage>,
+    ) -> Result<Client, NewSessionError> {
+        let session = self.opened.fetch_add(1, Ordering::Relaxed);
+        connect_webdriver(config, config.user_agent_for(session), tx).await
+    }
+
+    // An idle session, or a new one when every session is in use
+    async fn acquire(
+        &self,
+        config: &Config,
+        tx: &mpsc::Sender<CrawlerMessage>,
+    ) -> Result<Client, NewSessionError> {
+        if let Some(client) = self.idle.lock().await.pop() {
+            return Ok(client);
+        }
+        self.connect(config, tx).await
+    }
+
+    async fn release(&self, client: Client) {
+        self.idle.lock().await.push(client);
+    }
+
+    async fn close_all(&self) {
+        let clients = std::mem::take(&mut *self.idle.lock().await);
+        for client in clients {
+            if let Err(e) = client.close().await {
+                eprintln!("Error closing WebDriver client: {}", e);
+            }
+        }
+    }
+}
+
+// Errors after which the session is gone (browser crashed, driver restarted) and needs
+// replacing, as opposed to the page itself failing
+fn session_is_dead(error: &CmdError) -> bool {
+    match error {
+        CmdError::Lost(_) | CmdError::Failed(_) | CmdError::NoSuchWindow(_) => true,
+        CmdError::Standard(e) => e.error() == "invalid session id",
+        _ => false,
+    }
+}
+
+fn queue_contains(queue: &VecDeque<QueuedUrl>, url: &str) -> bool {
+    queue.iter().any(|queued| queued.url == url)
+}
+
+fn body_hash(body: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    hasher.finish()
+}
+
+// How many of the most recent page bodies loop detection remembers
+const RECENT_BODY_WINDOW: usize = 200;
+
+// Rolling record of recent body hashes, to spot the same content served under endless URLs
+struct RecentBodies {
+    order: VecDeque<u64>,
+    counts: HashMap<u64, usize>,
+}
+
+impl RecentBodies {
+    fn new() -> Self {
+        RecentBodies {
+            order: VecDeque::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    // Records a body and returns how often it now occurs within the window
+    fn record(&mut self, body: &str) -> usize {
+        let hash = body_hash(body);
+
+        if self.order.len() == RECENT_BODY_WINDOW
+            && let Some(oldest) = self.order.pop_front()
+            && let Some(count) = self.counts.get_mut(&oldest)
+        {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&oldest);
+            }
+        }
+        self.order.push_back(hash);
+        let count = self.counts.entry(hash).or_insert(0);
+        *count += 1;
+        *count
+    }
+}
+
+// Exponential moving average of page load times, for --adaptive-delay
+struct FetchTimes {
+    average: Option<Duration>,
+}
+
+impl FetchTimes {
+    fn new() -> Self {
+        FetchTimes { average: None }
+    }
+
+    fn record(&mut self, fetch_time: Duration) {
+        self.average = Some(match self.average {
+            // Weighted towards history so one slow page doesn't swing the delay
+            Some(average) => (average * 4 + fetch_time) / 5,
+            None => fetch_time,
+        });
+    }
+
+    // Waits about as long as the server has recently been taking to respond, so a server
+    // that slows down under load gets proportionally more breathing room.
+    fn adaptive_delay(&self, max_delay: Duration) -> Duration {
+        self.average
+            .unwrap_or(CRAWL_DELAY)
+            .clamp(CRAWL_DELAY, max_delay.max(CRAWL_DELAY))
+    }
+}
+
+// Everything extracted from one successfully loaded page
+#[derive(Serialize, Deserialize)]
+struct CrawledPage {
+    url: String,
+    #[serde(default)] // Sessions saved before titles were recorded
+    title: String,
+    #[serde(default)]
+    crawled_at: u64, // Seconds since the Unix epoch, 0 if unknown
+    #[serde(default)]
+    load_ms: u64, // Navigation plus text extraction, 0 if unknown
+    #[serde(default)]
+    final_url: Option<String>, // Where the browser ended up, when a redirect moved it
+    #[serde(default)]
+    parent: Option<String>, // The page whose link led here
+    #[serde(default)]
+    content_selector: Option<String>, // What the text was taken from; None for the whole body
+    depth: usize, // Link hops from the seed URL
+    body: String,
+    links: Vec<String>,              // Outbound links found on the page
+    json_ld: Vec<serde_json::Value>, // Parsed application/ld+json blocks (with --jsonld)
+    #[serde(default)]
+    anchors: Vec<(String, String)>, // Each link with its anchor text (with --anchor-text)
+    #[serde(default)]
+    html: Option<String>, // The page's source (with --capture-html)
+}
+
+enum CrawlerMessage {
+    Fetching(String),       // The crawler has started loading this URL
+    Discarded(String),      // Crawled for its links only: its text didn't match --keep-matching
+    Page(Box<CrawledPage>), // Boxed: it dwarfs the other variants
+    // The URL couldn't be crawled, or was skipped once loaded
+    Failed { url: String, error: CrawlError },
+    Status(Option<String>), // Progress note for the status bar, None clears it
+}
+
+// --- Crawl Errors ---
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum CrawlErrorKind {
+    Navigation,  // The browser couldn't load the URL
+    Timeout,     // The page didn't finish loading in time
+    SessionLost, // The WebDriver session died while loading it
+    NotHtml,     // Skipped by --html-only
+    NoBody,      // Skipped by --on-empty-body skip
+}
+
+impl CrawlErrorKind {
+    fn description(self) -> &'static str {
+        match self {
+            CrawlErrorKind::Navigation => "Navigation failed",
+            CrawlErrorKind::Timeout => "Timed out",
+            CrawlErrorKind::SessionLost => "WebDriver session lost",
+            CrawlErrorKind::NotHtml => "Not an HTML page",
+            CrawlErrorKind::NoBody => "No <body> element",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "SavedCrawlError")]
+struct CrawlError {
+    kind: CrawlErrorKind,
+    message: String,
+}
+
+// Sessions saved before errors had kinds stored just the message
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedCrawlError {
+    Detailed {
+        kind: CrawlErrorKind,
+        message: String,
+    },
+    Message(String),
+}
+
+impl From<SavedCrawlError> for CrawlError {
+    fn from(saved: SavedCrawlError) -> Self {
+        match saved {
+            SavedCrawlError::Detailed { kind, message } => CrawlError { kind, message },
+            SavedCrawlError::Message(message) => CrawlError {
+                kind: CrawlErrorKind::Navigation,
+                message,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for CrawlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind.description(), self.message)
+    }
+}
+
+fn navigation_error(error: &CmdError) -> CrawlError {
+    let kind = match error {
+        _ if session_is_dead(error) => CrawlErrorKind::SessionLost,
+        CmdError::WaitTimeout => CrawlErrorKind::Timeout,
+        CmdError::Standard(e) if e.error() == "timeout" => CrawlErrorKind::Timeout,
+        _ => CrawlErrorKind::Navigation,
+    };
+    CrawlError {
+        kind,
+        message: error.to_string(),
+    }
+}
+
+// WebDriver is often started alongside crabcrawl, so give a slow driver a few chances
+// with exponential backoff before giving up.
+async fn connect_webdriver(
+    config: &Config,
+    user_agent: Option<&str>,
+    tx: &mpsc::Sender<CrawlerMessage>,
+) -> Result<Client, NewSessionError> {
+    let mut delay = config.webdriver_retry_interval;
+    let mut attempt = 1;
+    loop {
+        let status = format!(
+            "Connecting to WebDriver, attempt {}/{}…",
+            attempt, config.webdriver_retries
+        );
+        tx.send(CrawlerMessage::Status(Some(status))).await.ok();
+
+        match ClientBuilder::native()
+            .capabilities(config.capabilities(user_agent))
+            .connect(WEBDRIVER_URL)
+            .await
+        {
+            Ok(client) => {
+                tx.send(CrawlerMessage::Status(None)).await.ok();
+                return Ok(client);
+            }
+            Err(e) if attempt >= config.webdriver_retries => return Err(e),
+            Err(_) => {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+async fn crawler_task(
+    context: Arc<WorkerContext>,
+    tx: mpsc::Sender<CrawlerMessage>,
+    stop: Arc<AtomicBool>,
+) {
+    let WorkerContext {
+        base_url,
+        url_queue,
+        visited,
+        control,
+        config,
+        sessions,
+        pages_per_domain,
+        started_at,
+        ..
+    } = &*context;
+    let mut client = match sessions.acquire(config, &tx).await {
+        Ok(c) => c,
+        Err(e) => {
+            let status = format!("Failed to connect to WebDriver at {}: {}", WEBDRIVER_URL, e);
+            tx.send(CrawlerMessage::Status(Some(status))).await.ok();
+            return;
+        }
+    };
+
+    let mut recent_bodies = RecentBodies::new();
+    let mut fetch_times = FetchTimes::new();
+    let mut rng = config.shuffle.then(|| match config.shuffle_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    });
+
+    while let Some((QueuedUrl { url, depth, parent }, _busy)) =
+        next_queued_url(&context, &stop).await
+    {
+        // Out of time: leave the rest of the queue untouched and finish
+        if config
+            .max_duration
+            .is_some_and(|limit| started_at.elapsed() >= limit)
+        {
+            visited.lock().await.remove(&url);
+            url_queue
+                .lock()
+                .await
+                .push_front(QueuedUrl { url, depth, parent });
+            let status = "Time limit reached, crawl stopped".to_string();
+            tx.send(CrawlerMessage::Status(Some(status))).await.ok();
+            break;
+        }
+
+        // Already queued before the domain filled up
+        if domain_is_full(&*pages_per_domain.lock().await, &url, config) {
+            continue;
+        }
+
+        // Every attempt counts, so failing pages can't multiply the load on a server
+        if let Some(budget) = config.max_requests
+            && !control.take_request(budget)
+        {
+            visited.lock().await.remove(&url);
+            url_queue
+                .lock()
+                .await
+                .push_front(QueuedUrl { url, depth, parent });
+            let status = format!("Request budget of {} used up, crawl stopped", budget);
+            tx.send(CrawlerMessage::Status(Some(status))).await.ok();
+            break;
+        }
+
+        if tx
+            .send(CrawlerMessage::Fetching(url.clone()))
+            .await
+            .is_err()
+        {
+            break;
+        }
+        let fetch_started = Instant::now();
+        let navigation = client.goto(&url).await;
+        fetch_times.record(fetch_started.elapsed());
+        if let Err(e) = navigation {
+            let session_died = session_is_dead(&e);
+            let failure = CrawlerMessage::Failed {
+                url,
+                error: navigation_error(&e),
+            };
+            if tx.send(failure).await.is_err() {
+                break;
+            }
+            // Replace the session; the dead one is dropped rather than returned to the pool
+            if session_died {
+                tx.send(CrawlerMessage::Status(Some(
+                    "WebDriver session lost, reconnecting".to_string(),
+                )))
+                .await
+                .ok();
+                match sessions.connect(config, &tx).await {
+                    Ok(new_client) => client = new_client,
+                    Err(e) => {
+                        let status = format!("Couldn't replace the WebDriver session: {}", e);
+                        tx.send(CrawlerMessage::Status(Some(status))).await.ok();
+                        return;
+                    }
+                }
+            }
+            continue;
+        }
+
+        if config.html_only
+            && let Some(content_type) = non_html_content_type(&client).await
+        {
+            let failure = CrawlerMessage::Failed {
+                url,
+                error: CrawlError {
+                    kind: CrawlErrorKind::NotHtml,
+                    message: format!("Not HTML ({}), skipped by --html-only", content_type),
+                },
+            };
+            if tx.send(failure).await.is_err() {
+                break;
+            }
+            continue;
+        }
+        // The queue keeps the requested URL; the landing page is only reported
+        let final_url = client
+            .current_url()
+            .await
+            .ok()
+            .map(|landed| canonicalize_url(landed, config))
+            .filter(|landed| *landed != url);
+        if let Some(host) = url_host(&url) {
+            *pages_per_domain.lock().await.entry(host).or_insert(0) += 1;
+        }
+
+        // On a timeout the page is extracted as it is; it may simply not have the element
+        if let Some(selector) = &config.wait_for_selector {
+            wait_for_selector(&client, selector, config.wait_timeout).await;
+        } else if config.spa_mode
+            && Url::parse(&url).is_ok_and(|url| url.fragment().is_some_and(is_hash_route))
+        {
+            tokio::time::sleep(SPA_ROUTE_SETTLE).await;
+        }
+
+        // Before --strip-scripts changes the DOM, so the copy has the page's markup intact
+        let html = if config.capture_html {
+            client.source().await.ok()
+        } else {
+            None
+        };
+        if config.strip_scripts {
+            strip_script_elements(&client).await;
+        }
+        let (body_text, content_selector) = match select_content(&client, config).await {
+            Some((text, selector)) => (text, Some(selector)),
+            None => match read_body_text(&client, &url).await {
+                Some(text) => (text, None),
+                None => match config.on_empty_body {
+                    EmptyBodyAction::Placeholder => (BODY_NOT_FOUND.to_string(), None),
+                    EmptyBodyAction::Retry => {
+                        tokio::time::sleep(EMPTY_BODY_RETRY_DELAY).await;
+                        let text = read_body_text(&client, &url)
+                            .await
+                            .unwrap_or_else(|| BODY_NOT_FOUND.to_string());
+                        (text, None)
+                    }
+                    EmptyBodyAction::Source => {
+                        let source = client
+                            .source()
+                            .await
+                            .unwrap_or_else(|_| BODY_NOT_FOUND.to_string());
+                        (source, None)
+                    }
+                    EmptyBodyAction::Skip => {
+                        let failure = CrawlerMessage::Failed {
+                            url,
+                            error: CrawlError {
+                                kind: CrawlErrorKind::NoBody,
+                                message: "No <body> element, skipped by --on-empty-body"
+                                    .to_string(),
+                            },
+                        };
+                        if tx.send(failure).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                },
+            },
+        };
+        let load_ms = fetch_started.elapsed().as_millis() as u64;
+
+        // Content-mirror traps: the page is kept but its links aren't followed
+        let repeated_content = config.loop_threshold.is_some_and(|threshold| {
+            !is_placeholder_body(&body_text) && recent_bodies.record(&body_text) > threshold
+        });
+        if repeated_content {
+            let status = format!(
+                "Same content seen repeatedly, not following links from {}",
+                url
+            );
+            tx.send(CrawlerMessage::Status(Some(status))).await.ok();
+        }
+
+        let anchors = collect_page_links(&client, base_url, &url, config).await;
+        let links: Vec<String> = anchors.iter().map(|(link, _)| link.clone()).collect();
+        let json_ld = if config.extract_json_ld {
+            collect_json_ld(&client).await
+        } else {
+            Vec::new()
+        };
+
+        let kept = config
+            .keep_matching
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&body_text));
+        let page = CrawledPage {
+            url: url.clone(),
+            title: client.title().await.unwrap_or_default(),
+            crawled_at: unix_now(),
+            load_ms,
+            final_url,
+            content_selector,
+            parent,
+            depth,
+            body: body_text,
+            links: links.clone(),
+            json_ld,
+            anchors: if config.anchor_text {
+                anchors
+            } else {
+                Vec::new()
+            },
+            html,
+        };
+        let message = if kept {
+            CrawlerMessage::Page(Box::new(page))
+        } else {
+            CrawlerMessage::Discarded(url.clone())
+        };
+        if let Err(e) = tx.send(message).await {
+            eprintln!("Failed to send crawl result to main thread: {}", e);
+            break;
+        }
+        control.count_page(config.pause_every);
+
+        // With link expansion stopped the crawl is bounded to the frontier already queued.
+        if control.is_expanding() && !repeated_content {
+            let next_page = if config.follow_pagination {
+                find_next_page(&client, base_url, config).await
+            } else {
+                None
+            };
+            let domain_counts = pages_per_domain.lock().await;
+            let in_scope = |link: &str| {
+                Url::parse(link).is_ok_and(|abs_url| is_in_scope(&abs_url, base_url, config))
+                    && !domain_is_full(&domain_counts, link, config)
+            };
+
+            let mut queue = url_queue.lock().await;
+            let visited_guard = visited.lock().await;
+            enqueue_links(
+                &mut queue,
+                links,
+                rng.as_mut(),
+                config.deterministic,
+                |link| in_scope(link) && !visited_guard.contains(link),
+                depth + 1,
+                &url,
+            );
+
+            // Jump the queue so paginated sequences are followed in order
+            if let Some(next_page) = next_page
+                && in_scope(&next_page)
+                && !visited_guard.contains(&next_page)
+            {
+                queue.retain(|queued| queued.url != next_page);
+                queue.push_front(QueuedUrl {
+                    url: next_page,
+                    depth: depth + 1,
+                    parent: Some(url.clone()),
+                });
+            }
+        }
+        let delay = if config.adaptive_delay {
+            fetch_times.adaptive_delay(config.max_delay)
+        } else {
+            CRAWL_DELAY
+        };
+        tokio::time::sleep(delay).await;
+    }
+
+    sessions.release(client).await;
+}
+
+// Appends a page's links that pass `wanted` and aren't queued yet, shuffled with --shuffle
+// or sorted with --deterministic, so the queue doesn't depend on the order the page
+// happens to list them in
+fn enqueue_links(
+    queue: &mut VecDeque<QueuedUrl>,
+    mut links: Vec<String>,
+    rng: Option<&mut StdRng>,
+    deterministic: bool,
+    wanted: impl Fn(&str) -> bool,
+    depth: usize,
+    parent: &str,
+) {
+    if let Some(rng) = rng {
+        links.shuffle(rng);
+    } else if deterministic {
+        links.sort_unstable();
+    }
+    for link in links {
+        if wanted(&link) && !queue_contains(queue, &link) {
+            queue.push_back(QueuedUrl {
+                url: link,
+                depth,
+                parent: Some(parent.to_string()),
+            });
+        }
+    }
+}
+
+// The browser's idea of what the server sent, unless it's HTML. A page whose type can't be
+// read is given the benefit of the doubt.
+async fn non_html_content_type(client: &Client) -> Option<String> {
+    let value = client
+        .execute("return document.contentType;", Vec::new())
+        .await
+        .ok()?;
+    let content_type = value.as_str()?.to_string();
+    let is_html = matches!(content_type.as_str(), "text/html" | "application/xhtml+xml");
+    (!is_html).then_some(content_type)
+}
+
+// --strip-scripts: some pages make script or style elements visible, or the driver reports
+// their text anyway, so they're taken out of the DOM before the text is read. JSON-LD
+// blocks stay for --jsonld.
+async fn strip_script_elements(client: &Client) {
+    const SCRIPT: &str = "document.querySelectorAll(\
+        'script:not([type=\"application/ld+json\"]), style, noscript'\
+    ).forEach(element => element.remove());";
+    client.execute(SCRIPT, Vec::new()).await.ok();
+}
+
+// Text of the page's <body>; None when it has no body element
+async fn read_body_text(client: &Client, url: &str) -> Option<String> {
+    let element = client.find(Locator::Css("body")).await.ok()?;
+    Some(match element.text().await {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error extracting text from <body> for {}: {}", url, e);
+            BODY_EXTRACTION_FAILED.to_string()
+        }
+    })
+}
+
+// Text of every element matching the --content-selector list, selector by selector in the
+// order given, separated by blank lines. None when nothing matched so the body is used instead.
+async fn collect_selector_text(client: &Client, selectors: &[String]) -> Option<String> {
+    let mut parts = Vec::new();
+    for selector in selectors {
+        let Ok(elements) = client.find_all(Locator::Css(selector)).await else {
+            continue;
+        };
+        for element in elements {
+            if let Ok(text) = element.text().await
+                && !text.trim().is_empty()
+            {
+                parts.push(text);
+            }
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+// Text from the --content-selector regions together, otherwise from the first
+// --fallback-selector that has any, with the selector(s) it came from. None means the
+// whole body should be used.
+async fn select_content(client: &Client, config: &Config) -> Option<(String, String)> {
+    if let Some(text) = collect_selector_text(client, &config.content_selectors).await {
+        return Some((text, config.content_selectors.join(", ")));
+    }
+    for selector in &config.fallback_selectors {
+        if let Some(text) = collect_selector_text(client, std::slice::from_ref(selector)).await {
+            return Some((text, selector.clone()));
+        }
+    }
+    None
+}
+
+// Polls until `selector` matches something on the current page; false if it never did
+async fn wait_for_selector(client: &Client, selector: &str, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if client.find(Locator::Css(selector)).await.is_ok() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(SELECTOR_POLL_INTERVAL).await;
+    }
+}
+
+// Absolute URLs of every anchor on the current page, deduplicated in document order, each
+// with the text of the first of its anchors that has any (only read with --anchor-text).
+// Links in --link-attr attributes follow those of the anchors.
+// Gathered before any shared lock is taken since each attribute read is a WebDriver round trip.
+async fn collect_page_links(
+    client: &Client,
+    base_url: &Url,
+    page_url: &str,
+    config: &Config,
+) -> Vec<(String, String)> {
+    let anchors = match client.find_all(Locator::Css("a")).await {
+        Ok(anchors) => anchors,
+        Err(e) => {
+            eprintln!("Error finding links on {}: {}", page_url, e);
+            return Vec::new();
+        }
+    };
+    let mut candidates = Vec::new();
+    for anchor in anchors {
+        if let Ok(Some(href)) = anchor.attr("href").await {
+            candidates.push((anchor, href));
+        }
+    }
+    for name in &config.link_attrs {
+        let selector = format!("[{}]", name);
+        let Ok(elements) = client.find_all(Locator::Css(&selector)).await else {
+            continue;
+        };
+        for element in elements {
+            if let Ok(Some(value)) = element.attr(name).await
+                && let Some(href) = attribute_link(name, &value)
+            {
+                candidates.push((element, href));
+            }
+        }
+    }
+
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut links: Vec<(String, String)> = Vec::new();
+    for (anchor, href) in candidates {
+        let Some(abs_url) = navigable_link(base_url, &href, config.spa_mode) else {
+            continue;
+        };
+        let abs_url_str = canonicalize_url(abs_url, config);
+        if abs_url_str == page_url {
+            continue;
+        }
+        let position = *positions.entry(abs_url_str.clone()).or_insert_with(|| {
+            links.push((abs_url_str, String::new()));
+            links.len() - 1
+        });
+        if config.anchor_text && links[position].1.is_empty() {
+            let text = anchor.text().await.unwrap_or_default();
+            links[position].1 = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+    }
+    links
+}
+
+// A link as written in a --link-attr attribute. Event handlers like onclick hold script,
+// so only a navigation in it counts: location.href = '/x', location.assign('/x'),
+// window.open('/x') and the like.
+fn attribute_link(name: &str, value: &str) -> Option<String> {
+    if !name.starts_with("on") {
+        return Some(value.trim().to_string()).filter(|link| !link.is_empty());
+    }
+    const CALLS: [&str; 4] = [
+        "location.assign(",
+        "location.replace(",
+        "window.open(",
+        "location",
+    ];
+    CALLS.iter().find_map(|call| {
+        let rest = &value[value.find(call)? + call.len()..];
+        // location, location.href and document.location are assigned: skip to the =
+        let rest = if call.ends_with('(') {
+            rest
+        } else {
+            let rest = rest.strip_prefix(".href").unwrap_or(rest).trim_start();
+            rest.strip_prefix('=')
+                .filter(|rest| !rest.starts_with('='))?
+        };
+        let rest = rest.trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '\'' | '"' | '`'))?;
+        let link = &rest[1..];
+        Some(link[..link.find(quote)?].to_string())
+    })
+}
+
+// Attribute names for --link-attr, which go into a CSS selector as they are
+fn is_attribute_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// Target of the page's <link rel="next"> or <a rel="next">, if it has one
+async fn find_next_page(client: &Client, base_url: &Url, config: &Config) -> Option<String> {
+    let element = client
+        .find(Locator::Css(
+            "link[rel~='next'][href], a[rel~='next'][href]",
+        ))
+        .await
+        .ok()?;
+    let href = element.attr("href").await.ok()??;
+    navigable_link(base_url, &href, config.spa_mode).map(|url| canonicalize_url(url, config))
+}
+
+fn url_host(url: &str) -> Option<String> {
+    Url::parse(url).ok()?.host_str().map(str::to_string)
+}
+
+// --max-pages-per-domain: the URL's host already has its share of crawled pages
+fn domain_is_full(pages_per_domain: &HashMap<String, usize>, url: &str, config: &Config) -> bool {
+    config.max_pages_per_domain.is_some_and(|limit| {
+        url_host(url).is_some_and(|host| pages_per_domain.get(&host).is_some_and(|&n| n >= limit))
+    })
+}
+
+// Whether the crawler should enqueue a discovered link on its own
+fn is_in_scope(url: &Url, base_url: &Url, config: &Config) -> bool {
+    url.domain()
+        .is_some_and(|domain| is_scope_domain(domain, base_url, config))
+        && !is_auth_url(url, &config.auth_skip_words)
+        && (!config.no_ascend || path_depth(url.path()) >= seed_path_depth(base_url.path()))
+        && (!config.subtree || url.path().starts_with(seed_directory(base_url.path())))
+        && config
+            .max_path_depth
+            .is_none_or(|max| path_depth(url.path()) <= max)
+}
+
+// The seed's host, unless --scope-domain names another
+fn is_scope_domain(domain: &str, base_url: &Url, config: &Config) -> bool {
+    match config.scope_domain.as_deref() {
+        Some(scope) => match scope.strip_prefix('.') {
+            Some(parent) => domain == parent || domain.ends_with(scope),
+            None => domain == scope,
+        },
+        None => Some(domain) == base_url.domain(),
+    }
+}
+
+// Non-empty path segments: /docs/guide/intro is 3
+fn path_depth(path: &str) -> usize {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .count()
+}
+
+// Depth of the directory the seed sits in, so /docs/guide/ and /docs/guide/index.html are both 2
+fn seed_path_depth(path: &str) -> usize {
+    path_depth(seed_directory(path))
+}
+
+// The seed's path up to and including its last slash: /docs/v2/intro.html -> /docs/v2/
+fn seed_directory(path: &str) -> &str {
+    &path[..path.rfind('/').map_or(0, |i| i + 1)]
+}
+
+// Heuristic match on the path and query, so hosts like login.example.com aren't caught
+fn is_auth_url(url: &Url, skip_words: &[String]) -> bool {
+    let target = format!("{}?{}", url.path(), url.query().unwrap_or("")).to_lowercase();
+    skip_words.iter().any(|word| target.contains(word.as_str()))
+}
+
+// An href as a page the crawler can load: http(s) only, so mailto:, tel:, javascript: and
+// the like are dropped, and without its fragment. Fragment-only hrefs point back at the
+// current page and are dropped too. With --spa-mode a hash route (#/inbox) is a page of
+// its own, so it's kept, fragment and all.
+fn navigable_link(base_url: &Url, href: &str, spa_mode: bool) -> Option<Url> {
+    let href = href.trim_start();
+    let is_route = |fragment: &str| spa_mode && is_hash_route(fragment);
+    if href
+        .strip_prefix('#')
+        .is_some_and(|fragment| !is_route(fragment))
+    {
+        return None;
     }
+    let mut url = base_url.join(href).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    if !url.fragment().is_some_and(is_route) {
+        url.set_fragment(None);
+    }
+    Some(url)
 }
 
-// --- Crawler Task (Unchanged) ---
-type CrawlerMessage = (String, String);
-async fn crawler_task(
-    base_url: Url,
-    tx: mpsc::Sender<CrawlerMessage>,
-    url_queue: Arc<Mutex<VecDeque<String>>>,
-    visited: Arc<Mutex<HashSet<String>>>,
-) {
-    let client = match Client::new(WEBDRIVER_URL).await {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Failed to connect to WebDriver at {}: {}", WEBDRIVER_URL, e);
-            return;
-        }
-    };
+// #/inbox or #!/inbox: a client-side route rather than an anchor within the page
+fn is_hash_route(fragment: &str) -> bool {
+    fragment.starts_with('/') || fragment.starts_with("!/")
+}
 
-    let base_domain = base_url.domain().unwrap_or("").to_string();
+// Time a single-page app gets to render a route after its fragment changes; there's no
+// page load to wait for. --wait-for-selector replaces it with a proper wait.
+const SPA_ROUTE_SETTLE: Duration = Duration::from_millis(500);
 
-    while let Some(url) = { url_queue.lock().await.pop_front() } {
-        if visited.lock().await.contains(&url) {
-            continue;
-        }
+// The form of a URL used for visited/queue dedup and everything downstream of it
+fn canonicalize_url(mut url: Url, config: &Config) -> String {
+    if config.sort_query_params {
+        sort_query_params(&mut url);
+    }
+    url.to_string()
+}
 
-        if let Err(e) = client.goto(&url).await {
-            eprintln!("Error navigating to {}: {}", url, e);
-            visited.lock().await.insert(url);
-            continue;
+// --display-url normalized: https://site.com/docs/ is shown as https://site.com/docs, while
+// https://site.com/ keeps its slash
+fn normalize_display_url(url: &str) -> Cow<'_, str> {
+    match Url::parse(url) {
+        Ok(mut parsed) if parsed.path().len() > 1 && parsed.path().ends_with('/') => {
+            let path = parsed.path().trim_end_matches('/').to_string();
+            parsed.set_path(&path);
+            Cow::Owned(parsed.to_string())
         }
+        _ => Cow::Borrowed(url),
+    }
+}
 
-        visited.lock().await.insert(url.clone());
-
-        let body_text = match client.find(Locator::Css("body")).await {
-            Ok(element) => match element.text().await {
-                Ok(text) => text,
-                Err(e) => {
-                    eprintln!("Error extracting text from <body> for {}: {}", url, e);
-                    "<Body text extraction failed>".to_string()
-                }
-            },
-            Err(_) => "<Body element not found>".to_string(),
-        };
+// `?b=2&a=1` and `?a=1&b=2` become the same URL; repeated keys keep a stable value order
+fn sort_query_params(url: &mut Url) {
+    if url.query().is_none() {
+        return;
+    }
+    let mut pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    if pairs.is_empty() {
+        url.set_query(None);
+        return;
+    }
+    // Stable and by key only: a=2&a=1 and a=1&a=2 stay different URLs
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
 
-        if let Err(e) = tx.send((url.clone(), body_text)).await {
-            eprintln!("Failed to send crawl result to main thread: {}", e);
-            break;
-        }
+// Structured data blocks on the current page; blocks that aren't valid JSON are skipped
+async fn collect_json_ld(client: &Client) -> Vec<serde_json::Value> {
+    let Ok(scripts) = client
+        .find_all(Locator::Css("script[type='application/ld+json']"))
+        .await
+    else {
+        return Vec::new();
+    };
 
-        match client.find_all(Locator::Css("a")).await {
-            Ok(links) => {
-                let mut queue = url_queue.lock().await;
-                let visited_guard = visited.lock().await;
-
-                for link in links {
-                    if let Ok(Some(href)) = link.attr("href").await {
-                        if let Ok(abs_url) = base_url.join(&href) {
-                            if abs_url.domain().map_or(false, |d| d == base_domain) {
-                                let abs_url_str = abs_url.to_string();
-                                if !visited_guard.contains(&abs_url_str)
-                                    && !queue.contains(&abs_url_str)
-                                {
-                                    queue.push_back(abs_url_str);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Error finding links on {}: {}", url, e);
-            }
+    let mut blocks = Vec::new();
+    for script in scripts {
+        // Scripts aren't rendered, so read the raw source rather than the visible text
+        if let Ok(source) = script.html(true).await
+            && let Ok(value) = serde_json::from_str(&source)
+        {
+            blocks.push(value);
         }
-        tokio::time::sleep(Duration::from_millis(50)).await;
-    }
-
-    if let Err(e) = client.close().await {
-        eprintln!("Error closing WebDriver client: {}", e);
     }
+    blocks
 }
 
 // --- TUI Rendering ---
 
 fn ui<B: tui::backend::Backend>(f: &mut Frame<B>, app_state: &mut AppState) {
+    // Below this the panels can't fit their borders, let alone any content
+    let size = f.size();
+    if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
+        let message = format!(
+            "Terminal too small ({}x{}), need at least {}x{}",
+            size.width, size.height, MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT
+        );
+        f.render_widget(Paragraph::new(message).wrap(Wrap { trim: true }), size);
+        return;
+    }
+
+    // Hidden bars collapse to zero height; the search bar reappears while typing a query
+    let typing = app_state.is_searching
+        || app_state.line_input.is_some()
+        || app_state.profile_input.is_some();
+    if app_state.fullscreen_content {
+        render_fullscreen_content(f, app_state, typing);
+        return;
+    }
+    let show_search_bar = app_state.show_search_bar || typing;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints(
             [
-                Constraint::Length(3),
+                Constraint::Length(if show_search_bar { 3 } else { 0 }),
                 Constraint::Min(1),
-                Constraint::Length(1),
+                Constraint::Length(if app_state.show_status_bar { 1 } else { 0 }),
             ]
             .as_ref(),
         )
         .split(f.size());
 
-    render_search_bar(f, app_state, chunks[0]);
+    if show_search_bar {
+        render_search_bar(f, app_state, chunks[0]);
+    }
     // Pass mutable state to render_main_content so it can update content_area
     render_main_content(f, app_state, chunks[1]);
-    render_status_bar(f, chunks[2]);
+    if app_state.show_status_bar {
+        render_status_bar(f, app_state, chunks[2]);
+    }
+
+    if let Some(popup) = app_state.popup {
+        render_popup(f, app_state, popup);
+    }
+}
+
+// Z: the content panel over the whole terminal, with the search bar only while typing
+fn render_fullscreen_content<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &mut AppState,
+    typing: bool,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(if typing { 3 } else { 0 }),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+    if typing {
+        render_search_bar(f, app_state, chunks[0]);
+    }
+    render_content_with_footer(f, app_state, chunks[1]);
+    if let Some(popup) = app_state.popup {
+        render_popup(f, app_state, popup);
+    }
+}
+
+// Crawler view keybindings listed by the help popup
+const KEY_HELP: &[(&str, &str)] = &[
+    ("j/k, ↑/↓", "Select next/previous page (or move the cursor)"),
+    ("]/[", "Next/previous failed page"),
+    ("Home", "Back to the seed URL (marked ⌂)"),
+    ("Backspace", "Up to the page that linked here"),
+    ("Tab", "Switch focus between list and content"),
+    (":", "Go to a line of the content"),
+    ("PgUp/PgDn, Mouse", "Scroll content"),
+    ("Ctrl+D/Ctrl+U", "Scroll content faster"),
+    ("/", "Search (Enter to apply, Esc to cancel)"),
+    ("Ctrl+R", "Pick a recent search"),
+    ("Esc", "Clear the active search"),
+    ("s", "Search bodies, titles or URLs"),
+    ("o", "Match whole words only"),
+    ("a", "Match the query as a phrase, any word or all words"),
+    ("n/N", "Next/previous match on this page"),
+    ("g", "Show only matching lines (grep view)"),
+    ("+/-", "Raise/lower the minimum body length"),
+    ("Y", "Copy the cursor line"),
+    ("v, y", "Select lines from the cursor and copy them"),
+    ("c", "Copy this crawl's settings as a command"),
+    ("O", "Copy this page's HTML (with --capture-html)"),
+    ("S", "Write a sitemap of the crawled pages"),
+    ("F", "Follow the bottom of the page"),
+    ("U", "Show requested / redirected URLs"),
+    ("t", "Relative / absolute crawl times"),
+    ("P", "Keep the first page selected as pages arrive"),
+    ("Ctrl+P", "Pin/unpin this page at the top of the list"),
+    ("w", "Word-wrap long lines"),
+    ("W", "Clean view: dedent and expand tabs"),
+    ("z", "Collapse repeated lines into one (×N)"),
+    ("G", "Dim lines found on many pages (nav, footers)"),
+    ("L", "Show a URL too long for the title in full"),
+    ("X", "Write the list and visible content to a file"),
+    ("K", "Save the crawl's options as a named profile"),
+    ("Ctrl+B", "Show/hide the URL list"),
+    ("Z", "Fullscreen content; j/k still change page"),
+    ("r", "Reverse the URL list (newest first)"),
+    ("T", "Sort the URL list by load time, slowest first"),
+    ("V", "Cycle flat, tree and unique-content list views"),
+    ("Enter", "Unique view: expand or fold the selected group"),
+    ("F2/F3", "Show/hide the search bar/status bar"),
+    ("H", "Show/hide the depth chart"),
+    ("D", "Show/hide pages per domain"),
+    ("h", "List only pages on this page's host, or all"),
+    ("i", "Crawl and page info"),
+    ("E", "Next failed page, with why it failed"),
+    ("l", "Links on this page (w/y there to export/copy)"),
+    ("p", "Pending queue: reorder, remove or clear"),
+    ("B", "Compare with the --baseline session"),
+    ("J", "JSON-LD on this page"),
+    ("M", "Harvest pattern matches across all pages"),
+    ("e", "Full URL of this page"),
+    ("x", "Stop/resume adding newly found links"),
+    ("Space", "Pause/resume the crawl"),
+    ("R", "Re-crawl every listed page"),
+    ("A", "Queue all uncrawled links and resume"),
+    (">/<", "Add a crawler worker / stop one"),
+    ("Ctrl+C", "Back to the URL prompt"),
+    ("Ctrl+Q", "Quit"),
+];
+
+// Rect of at most `width` x `height` centered within `area`
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn render_popup<B: tui::backend::Backend>(f: &mut Frame<B>, app_state: &AppState, popup: Popup) {
+    match popup {
+        Popup::FullUrl => {
+            let url = app_state
+                .get_selected_url_str()
+                .map_or(Cow::Borrowed("<None Selected>"), |url| {
+                    app_state.display_url(url)
+                });
+            let width = f.size().width.saturating_sub(8);
+            // Enough rows for the wrapped URL plus the borders
+            let height = (url.len() as u16 / width.saturating_sub(2).max(1)) + 3;
+            let area = centered_rect(width, height, f.size());
+            let paragraph = Paragraph::new(url).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Full URL (any key to close)"),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(paragraph, area);
+        }
+        Popup::Info => {
+            let mut lines = vec![
+                format!("Pages crawled:   {}", app_state.body_texts.len()),
+                format!("Text extracted:  {}", format_bytes(app_state.total_bytes)),
+                String::new(),
+            ];
+            match (
+                app_state.get_selected_url_str(),
+                app_state.get_selected_content(),
+            ) {
+                (Some(url), Some(content)) => {
+                    lines.push(format!(
+                        "URL:             {}",
+                        app_state.url_display.apply(url)
+                    ));
+                    if let Some(final_url) = app_state.final_urls.get(url) {
+                        lines.push(format!("Redirected to:   {}", final_url));
+                    }
+                    if let Some(depth) = app_state.depths.get(url) {
+                        lines.push(format!("Depth:           {}", depth));
+                    }
+                    if let Some(parent) = app_state.parents.get(url) {
+                        lines.push(format!("Found via:       {}", parent));
+                    }
+                    // WebDriver messages can run to a stack trace; the first line says it
+                    if let Some(error) = app_state.crawl_error(url) {
+                        lines.push(format!("Error:           {}", error.kind.description()));
+                        let reason = error.message.lines().next().unwrap_or_default();
+                        lines.push(format!("Reason:          {}", reason));
+                    } else if is_placeholder_body(content) {
+                        lines.push(format!("Error:           {}", content));
+                    }
+                    if let Some(title) = app_state.titles.get(url) {
+                        lines.push(format!("Title:           {}", title));
+                    }
+                    if let Some(replacements) = app_state.encoding_issues.get(url) {
+                        lines.push(format!(
+                            "Encoding:        {} replacement characters, may be mis-decoded",
+                            replacements
+                        ));
+                    }
+                    let launch_config = &app_state.launch_config;
+                    if !launch_config.content_selectors.is_empty()
+                        || !launch_config.fallback_selectors.is_empty()
+                    {
+                        let source = app_state
+                            .content_sources
+                            .get(url)
+                            .map_or("the whole body", String::as_str);
+                        lines.push(format!("Content from:    {}", source));
+                    }
+                    if app_state.crawled_at.contains_key(url) {
+                        lines.push(format!("Crawled:         {}", app_state.crawled_time(url)));
+                    }
+                    if let Some(&ms) = app_state.load_times.get(url) {
+                        lines.push(format!("Load time:       {}", format_millis(ms)));
+                    }
+                    lines.push(format!("Body size:       {}", format_bytes(content.len())));
+                    lines.push(format!("Lines:           {}", content.lines().count()));
+                }
+                _ => lines.push("No page selected.".to_string()),
+            }
+
+            let area = centered_rect(
+                f.size().width.saturating_sub(8),
+                lines.len() as u16 + 2,
+                f.size(),
+            );
+            let paragraph = Paragraph::new(lines.join("\n")).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Info (any key to close)"),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(paragraph, area);
+        }
+        Popup::Links => {
+            let queue = app_state.url_queue.try_lock().ok();
+            let items: Vec<ListItem> = app_state
+                .selected_page_links()
+                .iter()
+                .map(|link| {
+                    let marker = if app_state.body_texts.contains_key(link) {
+                        "✓"
+                    } else if queue.as_ref().is_some_and(|q| queue_contains(q, link)) {
+                        "…"
+                    } else {
+                        " "
+                    };
+                    let shown = app_state.url_display.apply(link);
+                    match app_state.anchor_text(link) {
+                        Some(text) => ListItem::new(format!("{} {}  \"{}\"", marker, shown, text)),
+                        None => ListItem::new(format!("{} {}", marker, shown)),
+                    }
+                })
+                .collect();
+            let area = centered_rect(
+                f.size().width.saturating_sub(8),
+                f.size().height.saturating_sub(6),
+                f.size(),
+            );
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(
+                    "Links (✓ crawled, … queued) Enter: open/queue, w: export, y: copy, Esc: close",
+                ))
+                .highlight_style(
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .bg(Color::DarkGray),
+                )
+                .highlight_symbol(">> ");
+            let mut links_state = app_state.links_state.clone();
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, &mut links_state);
+        }
+        Popup::StructuredData => {
+            let area = centered_rect(
+                f.size().width.saturating_sub(8),
+                f.size().height.saturating_sub(4),
+                f.size(),
+            );
+            let paragraph = Paragraph::new(app_state.selected_json_ld_text())
+                .scroll((app_state.popup_scroll, 0))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("JSON-LD (j/k: scroll, w: export all, Esc: close)"),
+                );
+            f.render_widget(Clear, area);
+            f.render_widget(paragraph, area);
+        }
+        Popup::RecentQueries => {
+            let items: Vec<ListItem> = app_state
+                .search_history
+                .iter()
+                .map(|query| ListItem::new(query.as_str()))
+                .collect();
+            let height = (items.len() as u16 + 2).min(f.size().height.saturating_sub(4));
+            let area = centered_rect(f.size().width.saturating_sub(8).min(72), height, f.size());
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Recent searches (Enter: apply, Esc: close)"),
+                )
+                .highlight_style(
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .bg(Color::DarkGray),
+                )
+                .highlight_symbol(">> ");
+            let mut history_state = app_state.history_state.clone();
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, &mut history_state);
+        }
+        Popup::Queue => {
+            let items: Vec<ListItem> = app_state
+                .queue_snapshot
+                .iter()
+                .enumerate()
+                .map(|(i, url)| ListItem::new(format!("[{}] {}", i + 1, url)))
+                .collect();
+            let area = centered_rect(
+                f.size().width.saturating_sub(8),
+                f.size().height.saturating_sub(6),
+                f.size(),
+            );
+            let title = format!(
+                "Queue ({}) Enter: crawl next, d: remove, C: clear all, Esc: close",
+                app_state.queue_snapshot.len()
+            );
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .bg(Color::DarkGray),
+                )
+                .highlight_symbol(">> ");
+            let mut queue_state = app_state.queue_state.clone();
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, &mut queue_state);
+        }
+        Popup::Baseline => render_baseline_popup(f, app_state),
+        Popup::Harvest => {
+            let area = centered_rect(
+                f.size().width.saturating_sub(8),
+                f.size().height.saturating_sub(4),
+                f.size(),
+            );
+            let title = format!(
+                "Harvest: {} matches of {} (j/k: scroll, w: export, Esc: close)",
+                app_state.harvested.len(),
+                app_state.harvest_pattern
+            );
+            let paragraph = Paragraph::new(app_state.harvest_text())
+                .scroll((app_state.popup_scroll, 0))
+                .block(Block::default().borders(Borders::ALL).title(title));
+            f.render_widget(Clear, area);
+            f.render_widget(paragraph, area);
+        }
+        Popup::Help => {
+            let lines: Vec<String> = KEY_HELP
+                .iter()
+                .map(|(keys, action)| format!("{:<16} {}", keys, action))
+                .collect();
+            let area = centered_rect(72, lines.len() as u16 + 2, f.size());
+            let paragraph = Paragraph::new(lines.join("\n")).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Keys (any key to close)"),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(paragraph, area);
+        }
+        Popup::Summary => {
+            let mut lines = vec![
+                format!("Pages crawled:   {}", app_state.body_texts.len()),
+                format!("Errors:          {}", app_state.error_count()),
+                format!("Domains:         {}", app_state.unique_domains().len()),
+                format!("Text extracted:  {}", format_bytes(app_state.total_bytes)),
+                format!(
+                    "Elapsed:         {}",
+                    format_duration(app_state.crawl_elapsed())
+                ),
+            ];
+            if let Some(&last) = app_state.crawled_at.values().max() {
+                lines.push(format!(
+                    "Last page:       {}",
+                    format_timestamp(last, app_state.time_display)
+                ));
+            }
+            if app_state.check_encoding {
+                lines.push(format!(
+                    "Encoding issues: {}",
+                    app_state.encoding_issues.len()
+                ));
+            }
+            if app_state.memory_cap.is_some() {
+                lines.push(format!("Evicted:         {}", app_state.evicted_urls.len()));
+            }
+            if app_state.launch_config.keep_matching.is_some() {
+                lines.push(format!("Not kept:        {}", app_state.discarded_pages));
+            }
+            if app_state.baseline.is_some() {
+                lines.push(format!(
+                    "Vs baseline:     {} changed, {} added, {} removed",
+                    app_state.baseline_count(BaselineChange::Changed),
+                    app_state.baseline_count(BaselineChange::Added),
+                    app_state.baseline_count(BaselineChange::Removed)
+                ));
+            }
+            // The baseline line can outgrow the usual width with large counts
+            let width = lines
+                .iter()
+                .map(|line| line.len() + 2)
+                .max()
+                .unwrap_or(0)
+                .max(50);
+            let area = centered_rect(width as u16, lines.len() as u16 + 2, f.size());
+            let paragraph = Paragraph::new(lines.join("\n")).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Crawl finished (any key to close)"),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(paragraph, area);
+        }
+    }
+}
+
+// Counts of each kind of change above a list of the pages that changed
+fn render_baseline_popup<B: tui::backend::Backend>(f: &mut Frame<B>, app_state: &AppState) {
+    let Some(baseline) = &app_state.baseline else {
+        return;
+    };
+    let area = centered_rect(
+        f.size().width.saturating_sub(8),
+        f.size().height.saturating_sub(4),
+        f.size(),
+    );
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(0)])
+        .split(area);
+
+    let counts: Vec<String> = [
+        BaselineChange::Changed,
+        BaselineChange::Added,
+        BaselineChange::Removed,
+        BaselineChange::Unchanged,
+    ]
+    .iter()
+    .map(|&kind| {
+        let label = format!("{}:", kind.label());
+        format!("{:<11} {}", label, app_state.baseline_count(kind))
+    })
+    .collect();
+    let mut title = format!(
+        "Vs baseline of {} from {}",
+        baseline.seed_url,
+        format_timestamp(baseline.saved_at, app_state.time_display)
+    );
+    if app_state.crawl_finished_at.is_none() {
+        title.push_str(" (crawl still running)");
+    }
+    let summary = Paragraph::new(counts.join("\n"))
+        .block(Block::default().borders(Borders::ALL).title(title));
+
+    let items: Vec<ListItem> = app_state
+        .baseline_listed()
+        .iter()
+        .map(|(change, url)| {
+            ListItem::new(format!(
+                "{:<8} {}",
+                change.label(),
+                app_state.display_url(url)
+            ))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Pages (Enter: show, Esc: close)"),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(Color::DarkGray),
+        )
+        .highlight_symbol(">> ");
+    let mut baseline_state = app_state.baseline_state.clone();
+    f.render_widget(Clear, area);
+    f.render_widget(summary, chunks[0]);
+    f.render_stateful_widget(list, chunks[1], &mut baseline_state);
+}
+
+// Compact duration, e.g. "1h02m", "4m12s" or "9s"
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+// Page load time, e.g. "850 ms" or "2.4 s"
+fn format_millis(ms: u64) -> String {
+    if ms >= 1000 {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    } else {
+        format!("{} ms", ms)
+    }
+}
+
+// Human readable byte count, e.g. "2.3 MB"
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
 }
 
 fn render_search_bar<B: tui::backend::Backend>(f: &mut Frame<B>, app_state: &AppState, area: Rect) {
+    if let Some(input) = &app_state.profile_input {
+        let prefix = "Save the crawl's options as profile: ";
+        let widget = Paragraph::new(format!("{}{}", prefix, input.as_str()))
+            .style(Style::default().fg(Color::Yellow))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Save profile (Enter: save, Esc: cancel)"),
+            );
+        f.render_widget(widget, area);
+        let x = area.x + 1 + prefix.width() as u16;
+        place_input_cursor(f, area, x, area.y + 1, input);
+        return;
+    }
+    if let Some(input) = &app_state.line_input {
+        let line_count = app_state
+            .get_selected_content()
+            .map_or(0, |content| content.lines().count());
+        let prefix = format!("Go to line (1-{}): ", line_count);
+        let widget = Paragraph::new(format!("{}{}", prefix, input.as_str()))
+            .style(Style::default().fg(Color::Yellow))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Go to line (Enter: jump, Esc: cancel)"),
+            );
+        f.render_widget(widget, area);
+        let x = area.x + 1 + prefix.width() as u16;
+        place_input_cursor(f, area, x, area.y + 1, input);
+        return;
+    }
+    let whole_word = format!(
+        "{}{}",
+        if app_state.whole_word {
+            " (whole words)"
+        } else {
+            ""
+        },
+        app_state.term_mode.label()
+    );
+    let search_prefix = format!("Search {}{}: ", app_state.search_scope.label(), whole_word);
     let search_text = if app_state.is_searching {
-        format!("Search: {}", app_state.search_input)
+        format!("{}{}", search_prefix, app_state.search_input.as_str())
     } else if !app_state.active_search_query.is_empty() {
         format!(
-            "Filtering by: \"{}\" (Press '/' to edit, Esc to clear)",
-            app_state.active_search_query
+            "Filtering {} by: \"{}\"{} (Press '/' to edit, Esc to clear, s: scope, o: whole words, a: any/all)",
+            app_state.search_scope.label(),
+            app_state.active_search_query,
+            whole_word
         )
     } else {
         "Press '/' to search".to_string()
     };
+    let search_text = if app_state.min_body_chars > 0 {
+        format!(
+            "{} | Min body: {} chars (+/-)",
+            search_text, app_state.min_body_chars
+        )
+    } else {
+        search_text
+    };
 
     let search_widget = Paragraph::new(search_text)
         .style(if app_state.is_searching {
@@ -363,6 +6464,10 @@ fn render_search_bar<B: tui::backend::Backend>(f: &mut Frame<B>, app_state: &App
         })
         .block(Block::default().borders(Borders::ALL).title("Search"));
     f.render_widget(search_widget, area);
+    if app_state.is_searching {
+        let x = area.x + 1 + search_prefix.width() as u16;
+        place_input_cursor(f, area, x, area.y + 1, &app_state.search_input);
+    }
 }
 
 // This function now takes &mut AppState to update the content_area
@@ -371,17 +6476,201 @@ fn render_main_content<B: tui::backend::Backend>(
     app_state: &mut AppState,
     area: Rect,
 ) {
+    let area = if app_state.show_depth_chart {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(10)].as_ref())
+            .split(area);
+        render_depth_chart(f, app_state, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+    let area = if app_state.show_domains {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(40)].as_ref())
+            .split(area);
+        render_domains_panel(f, app_state, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    // With the list collapsed the content view gets the full width
+    if !app_state.show_url_list {
+        render_content_with_footer(f, app_state, area);
+        return;
+    }
+
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
         .split(area);
 
+    render_url_list(f, app_state, content_chunks[0]);
+    render_content_with_footer(f, app_state, content_chunks[1]);
+}
+
+// The content panel with a one-row footer of page statistics under it, when there's room
+fn render_content_with_footer<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &mut AppState,
+    area: Rect,
+) {
+    let (area, url_area) = full_url_rows(app_state, area);
+    if let Some(url_area) = url_area
+        && let Some(url) = app_state.get_selected_url_str()
+    {
+        let paragraph = Paragraph::new(app_state.display_url(url))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Color::Cyan));
+        f.render_widget(paragraph, url_area);
+    }
+    let (content_area, footer_area) = if area.height > CONTENT_FOOTER_MIN_HEIGHT {
+        let content_area = Rect {
+            height: area.height - 1,
+            ..area
+        };
+        let footer_area = Rect {
+            y: area.bottom() - 1,
+            height: 1,
+            ..area
+        };
+        (content_area, Some(footer_area))
+    } else {
+        (area, None)
+    };
+
     // Update the content area in the state *before* rendering it
     // This ensures the mouse handler uses the correct area from the *current* frame
-    app_state.content_area = content_chunks[1];
+    app_state.content_area = content_area;
+    render_content_view(f, app_state, content_area);
+    if let Some(footer_area) = footer_area {
+        render_content_footer(f, app_state, footer_area);
+    }
+}
 
-    render_url_list(f, app_state, content_chunks[0]);
-    render_content_view(f, app_state, content_chunks[1]); // Pass immutable ref here is fine now
+// Below this the footer row would crowd out the content
+const CONTENT_FOOTER_MIN_HEIGHT: u16 = 6;
+
+// Most rows the full URL line (L) may take above the content
+const FULL_URL_MAX_ROWS: u16 = 3;
+
+// With L on, splits off rows above the content panel for a URL the title cuts off
+fn full_url_rows(app_state: &AppState, area: Rect) -> (Rect, Option<Rect>) {
+    let title_width = area.width.saturating_sub(2) as usize;
+    if !app_state.full_url_line || title_width == 0 {
+        return (area, None);
+    }
+    if content_title(app_state).chars().count() <= title_width {
+        return (area, None);
+    }
+    let url_chars = app_state
+        .get_selected_url_str()
+        .map_or(0, |url| app_state.display_url(url).chars().count());
+    let rows = (url_chars.div_ceil(area.width as usize) as u16).min(FULL_URL_MAX_ROWS);
+    if area.height <= CONTENT_FOOTER_MIN_HEIGHT + rows {
+        return (area, None);
+    }
+    let url_area = Rect {
+        height: rows,
+        ..area
+    };
+    let rest = Rect {
+        y: area.y + rows,
+        height: area.height - rows,
+        ..area
+    };
+    (rest, Some(url_area))
+}
+
+// "Content (Scroll: 12): https://site.com/page", as far as --scroll-indicator wants it
+fn content_title(app_state: &AppState) -> String {
+    let selected_url_str = app_state
+        .get_selected_url_str()
+        .map_or(Cow::Borrowed("<None Selected>"), |url| {
+            app_state.display_url(url)
+        });
+    match app_state.scroll_indicator {
+        ScrollIndicator::Title => format!(
+            "Content (Scroll: {}): {}",
+            app_state.content_scroll, selected_url_str
+        ),
+        ScrollIndicator::Corner | ScrollIndicator::Hidden => selected_url_str.to_string(),
+    }
+}
+
+// "1532 words · 87 lines · 4 matches" for the selected page
+fn render_content_footer<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &AppState,
+    area: Rect,
+) {
+    let Some(content) = app_state.get_selected_content() else {
+        return;
+    };
+    let mut footer = format!(
+        " {} words · {} lines",
+        content.split_whitespace().count(),
+        content.lines().count()
+    );
+    if !app_state.content_query().is_empty() {
+        let matches = app_state.match_positions().len();
+        match app_state.current_match.filter(|&current| current < matches) {
+            Some(current) => footer.push_str(&format!(" · match {}/{}", current + 1, matches)),
+            None => footer.push_str(&format!(" · {} matches", matches)),
+        }
+    }
+    let style = Style::default().fg(Color::DarkGray);
+    f.render_widget(Paragraph::new(footer).style(style), area);
+}
+
+fn render_depth_chart<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &AppState,
+    area: Rect,
+) {
+    let histogram = app_state.depth_histogram();
+    let labels: Vec<String> = (0..histogram.len())
+        .map(|depth| depth.to_string())
+        .collect();
+    let data: Vec<(&str, u64)> = labels
+        .iter()
+        .map(String::as_str)
+        .zip(histogram.iter().copied())
+        .collect();
+
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Pages per depth (H to hide)"),
+        )
+        .data(&data)
+        .bar_width(5)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    f.render_widget(chart, area);
+}
+
+fn render_domains_panel<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &AppState,
+    area: Rect,
+) {
+    let counts = app_state.domain_page_counts();
+    let items: Vec<ListItem> = counts
+        .iter()
+        .map(|(host, pages)| ListItem::new(format!("{:>5}  {}", pages, host)))
+        .collect();
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Domains ({}) D to hide", counts.len())),
+    );
+    f.render_widget(list, area);
 }
 
 fn render_url_list<B: tui::backend::Backend>(
@@ -389,17 +6678,54 @@ fn render_url_list<B: tui::backend::Backend>(
     app_state: &mut AppState,
     area: Rect,
 ) {
+    let spinner = spinner_frame(app_state.crawl_started_at);
     let displayed_urls = app_state.get_displayed_urls();
     let items: Vec<ListItem> = displayed_urls
         .iter()
         .enumerate()
         .map(|(i, url)| {
-            let display_url = if url.len() > area.width.saturating_sub(6) as usize {
-                format!("{}...", &url[..area.width.saturating_sub(9) as usize])
-            } else {
-                url.to_string()
+            let indent = "  ".repeat(app_state.tree_depths.get(i).copied().unwrap_or(0));
+            let shared = match app_state.group_sizes.get(i) {
+                Some(&count) if count > 1 => repeat_marker(count),
+                _ => String::new(),
             };
-            ListItem::new(Span::raw(format!("[{}] {}", i + 1, display_url)))
+            // Room left after the borders and the highlight symbol
+            let label = truncate_with_ellipsis(
+                &format!(
+                    "{}{}{}",
+                    indent,
+                    app_state.format_list_item(i + 1, url),
+                    shared
+                ),
+                area.width.saturating_sub(6) as usize,
+            );
+            let pinned = app_state
+                .filtered_url_indices
+                .get(i)
+                .is_some_and(|idx| app_state.pinned.contains(idx));
+            if pinned {
+                ListItem::new(Spans::from(vec![
+                    Span::styled("◆ ", Style::default().fg(Color::LightYellow)),
+                    Span::raw(label),
+                ]))
+            } else if **url == app_state.seed_url {
+                ListItem::new(Spans::from(vec![
+                    Span::styled("⌂ ", Style::default().fg(Color::Cyan)),
+                    Span::raw(label),
+                ]))
+            } else if app_state.encoding_issues.contains_key(url.as_str()) {
+                ListItem::new(Spans::from(vec![
+                    Span::styled("? ", Style::default().fg(Color::Magenta)),
+                    Span::raw(label),
+                ]))
+            } else if app_state.body_texts.contains_key(url.as_str()) {
+                ListItem::new(Span::raw(label))
+            } else {
+                ListItem::new(Spans::from(vec![
+                    Span::styled(format!("{} ", spinner), Style::default().fg(Color::Yellow)),
+                    Span::raw(label),
+                ]))
+            }
         })
         .collect();
 
@@ -407,6 +6733,7 @@ fn render_url_list<B: tui::backend::Backend>(
         .block(
             Block::default()
                 .borders(Borders::ALL)
+                .border_style(focus_border_style(app_state, Focus::List))
                 .title(format!("Visited URLs ({})", displayed_urls.len())),
         )
         .highlight_style(
@@ -419,70 +6746,388 @@ fn render_url_list<B: tui::backend::Backend>(
     f.render_stateful_widget(list_widget, area, &mut app_state.list_state);
 }
 
+// U+FFFD characters left behind by a lossy decode of the body
+fn replacement_char_count(text: &str) -> usize {
+    text.chars()
+        .filter(|&c| c == char::REPLACEMENT_CHARACTER)
+        .count()
+}
+
+// A few stray U+FFFD are normal; mojibake shows up as several, densely
+fn looks_mis_decoded(replacements: usize, text: &str) -> bool {
+    replacements >= ENCODING_MIN_REPLACEMENTS
+        && replacements * ENCODING_CHARS_PER_REPLACEMENT >= text.chars().count()
+}
+
+// Cuts `text` to at most `max_chars` characters, marking the cut with "..."
+fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+// Braille spinner advancing every 100ms, shared by everything still in flight
+fn spinner_frame(since: Instant) -> char {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    FRAMES[(since.elapsed().as_millis() / 100) as usize % FRAMES.len()]
+}
+
+// Highlights the border of the panel that currently has focus
+fn focus_border_style(app_state: &AppState, panel: Focus) -> Style {
+    if app_state.focus == panel {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    }
+}
+
 // Takes immutable AppState now, as content_area is set in the parent
 fn render_content_view<B: tui::backend::Backend>(
     f: &mut Frame<B>,
     app_state: &AppState,
     area: Rect,
 ) {
-    let selected_url_str = app_state
-        .get_selected_url_str()
-        .unwrap_or("<None Selected>");
-    let content_title = format!(
-        "Content (Scroll: {}): {}",
-        app_state.content_scroll, selected_url_str
+    // A title cut off by the border says so; L spells the URL out above the panel
+    let content_title = truncate_with_ellipsis(
+        &content_title(app_state),
+        area.width.saturating_sub(2) as usize,
     );
-    let block = Block::default().borders(Borders::ALL).title(content_title);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(focus_border_style(app_state, Focus::Content))
+        .title(content_title);
 
-    let text = if let Some(content_raw) = app_state.get_selected_content() {
-        if app_state.active_search_query.is_empty() {
-            Text::from(content_raw.as_str())
-        } else {
-            // Highlighting logic (unchanged)
-            let query = &app_state.active_search_query;
-            let query_lower = query.to_lowercase();
-            let mut spans_vec = Vec::new();
-            for line in content_raw.lines() {
-                let mut line_spans = Vec::new();
+    // --max-render-lines: only a window of source lines starting at the top of the view
+    // is built each frame, and the paragraph is scrolled within it
+    let window_start = match app_state.max_render_lines {
+        Some(_) => app_state.line_at_row(app_state.content_scroll as usize),
+        None => 0,
+    };
+    let window_len = app_state.max_render_lines.unwrap_or(usize::MAX);
+    let displayed = app_state.displayed_content();
+    let mut text = if let Some(content_raw) = displayed.as_deref() {
+        let query = app_state.content_query();
+        let query_lower = query.to_lowercase();
+        let terms = search_terms(&query_lower, app_state.term_mode);
+        let mut spans_vec = Vec::new();
+        for full_line in content_raw.lines().skip(window_start).take(window_len) {
+            // Unwrapped, anything past the panel is off screen anyway; capping it keeps a
+            // single huge line (minified JSON, say) from being laid out in full every frame
+            let (line, hidden_chars) = if app_state.wrap_lines {
+                (full_line, 0)
+            } else {
+                cap_line(full_line, MAX_RENDERED_LINE_CHARS)
+            };
+            let mut line_spans = Vec::new();
+            if query.is_empty() {
+                if !line.is_empty() {
+                    line_spans.push(Span::raw(line));
+                }
+            } else {
                 let mut last_match_end = 0;
                 let line_lower = line.to_lowercase();
-                for (start_idx, _) in line_lower.match_indices(&query_lower) {
-                    let end_idx = start_idx + query.len();
+                for (start_idx, end_idx) in term_matches(&line_lower, &terms, app_state.whole_word)
+                {
                     if start_idx > last_match_end {
                         line_spans.push(Span::raw(&line[last_match_end..start_idx]));
                     }
                     line_spans.push(Span::styled(
                         &line[start_idx..end_idx],
-                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        Style::default()
+                            .fg(app_state.highlight_color)
+                            .add_modifier(Modifier::BOLD),
                     ));
                     last_match_end = end_idx;
                 }
                 if last_match_end < line.len() {
                     line_spans.push(Span::raw(&line[last_match_end..]));
                 }
-                spans_vec.push(Spans::from(line_spans));
             }
-            Text::from(spans_vec)
+            // Search highlights stay bright on a dimmed line
+            if app_state.dim_boilerplate && app_state.is_boilerplate_line(full_line) {
+                for span in line_spans
+                    .iter_mut()
+                    .filter(|span| span.style == Style::default())
+                {
+                    span.style = Style::default().fg(Color::DarkGray);
+                }
+            }
+            if hidden_chars > 0 {
+                line_spans.push(Span::styled(
+                    format!(" … {} more chars (w to wrap)", hidden_chars),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            spans_vec.push(Spans::from(line_spans));
         }
+        Text::from(spans_vec)
+    } else if app_state.get_selected_url_str().is_some() {
+        Text::from("Loading...")
     } else {
         Text::from("Select a URL to view its content.")
     };
 
-    let content_widget = Paragraph::new(text)
-        .block(block)
-        .scroll((app_state.content_scroll, 0));
+    // Shade a visual-line selection, then mark the cursor line on top of it
+    if let Some((first, last)) = app_state.selected_line_range() {
+        let (first, last) = (first as usize, last as usize);
+        for line in text
+            .lines
+            .iter_mut()
+            .enumerate()
+            .filter(|(idx, _)| (first..=last).contains(&(window_start + idx)))
+            .map(|(_, line)| line)
+        {
+            if line.0.is_empty() {
+                line.0.push(Span::raw(" "));
+            }
+            for span in line.0.iter_mut() {
+                span.style = span.style.patch(Style::default().bg(Color::Blue));
+            }
+        }
+    }
+
+    // Mark the cursor line while the content panel has focus
+    if app_state.focus == Focus::Content
+        && app_state.get_selected_content().is_some()
+        && let Some(cursor) = (app_state.content_cursor as usize).checked_sub(window_start)
+        && let Some(line) = text.lines.get_mut(cursor)
+    {
+        if line.0.is_empty() {
+            line.0.push(Span::raw(" "));
+        }
+        for span in line.0.iter_mut() {
+            span.style = span.style.patch(Style::default().bg(Color::DarkGray));
+        }
+    }
+
+    // Drop hidden lines last so the markers above still index source lines
+    if let Some(visible) = app_state.visible_lines() {
+        let repeats = app_state.repeat_counts();
+        let mut shown = Vec::new();
+        // Whether a group of lines was drawn above the window
+        let shown_before = visible[..window_start.min(visible.len())].contains(&true);
+        for (offset, mut line) in std::mem::take(&mut text.lines).into_iter().enumerate() {
+            let idx = window_start + offset;
+            if !visible.get(idx).copied().unwrap_or(false) {
+                continue;
+            }
+            if (shown_before || !shown.is_empty())
+                && grep_gap_before(&visible, repeats.as_deref(), idx)
+            {
+                shown.push(Spans::from(Span::styled(
+                    "--",
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if let Some(&count) = repeats.as_ref().and_then(|repeats| repeats.get(idx))
+                && count > 1
+            {
+                line.0.push(Span::styled(
+                    repeat_marker(count),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            shown.push(line);
+        }
+        text.lines = shown;
+    }
+
+    // Reserve the rightmost inner column for the scrollbar
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if app_state.scroll_indicator == ScrollIndicator::Corner
+        && app_state.get_selected_content().is_some()
+    {
+        render_scroll_corner(f, app_state, area);
+    }
+    let text_area = Rect {
+        width: inner.width.saturating_sub(1),
+        ..inner
+    };
+    let scrollbar_area = Rect {
+        x: inner.x + text_area.width,
+        width: inner.width.min(1),
+        ..inner
+    };
+
+    let window_scroll =
+        (app_state.content_scroll as usize).saturating_sub(app_state.row_of_line(window_start));
+    let mut content_widget = Paragraph::new(text).scroll((window_scroll as u16, 0));
+    if app_state.wrap_lines {
+        content_widget = content_widget.wrap(Wrap { trim: false });
+    }
+
+    f.render_widget(content_widget, text_area);
+    render_content_scrollbar(f, app_state, scrollbar_area);
+}
+
+// " row/rows " over the right end of the content panel's bottom border
+fn render_scroll_corner<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &AppState,
+    area: Rect,
+) {
+    let label = format!(
+        " {}/{} ",
+        app_state.content_scroll as usize + 1,
+        app_state.total_content_rows().max(1)
+    );
+    let width = (label.len() as u16).min(area.width.saturating_sub(2));
+    if width == 0 || area.height < 2 {
+        return;
+    }
+    let corner = Rect {
+        x: area.right() - 1 - width,
+        y: area.bottom() - 1,
+        width,
+        height: 1,
+    };
+    let style = focus_border_style(app_state, Focus::Content);
+    f.render_widget(Paragraph::new(label).style(style), corner);
+}
+
+// One-column overview of the selected page: the thumb marks the visible window,
+// highlighted rows contain search matches.
+fn render_content_scrollbar<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app_state: &AppState,
+    area: Rect,
+) {
+    // In visual rows, so the thumb matches what's on screen when wrapping
+    let total_lines = app_state.total_content_rows();
+    if area.width == 0 || area.height == 0 || total_lines == 0 {
+        return;
+    }
+
+    // Matches hidden in a collapsed run are already marked by the line drawn for it
+    let visible = app_state.visible_lines();
+    let match_lines: Vec<usize> = app_state
+        .find_match_lines()
+        .into_iter()
+        .filter(|&line| visible.as_ref().is_none_or(|visible| visible[line]))
+        .map(|line| app_state.row_of_line(line))
+        .collect();
+    let height = area.height as usize;
+    let scroll = app_state.content_scroll as usize;
+
+    let rows: Vec<Spans> = (0..height)
+        .map(|row| {
+            // Each scrollbar row stands for a slice of the content's lines
+            let start = row * total_lines / height;
+            let end = ((row + 1) * total_lines / height).max(start + 1);
+            let in_thumb = start < scroll + height && end > scroll;
+            let first_match = match_lines.partition_point(|&line| line < start);
+            let has_match = match_lines.get(first_match).is_some_and(|&line| line < end);
+
+            let (symbol, color) = match (in_thumb, has_match) {
+                (true, true) => ("█", app_state.highlight_color),
+                (true, false) => ("█", Color::White),
+                (false, true) => ("▪", app_state.highlight_color),
+                (false, false) => ("│", Color::DarkGray),
+            };
+            Spans::from(Span::styled(symbol, Style::default().fg(color)))
+        })
+        .collect();
 
-    f.render_widget(content_widget, area);
+    f.render_widget(Paragraph::new(rows), area);
 }
 
-fn render_status_bar<B: tui::backend::Backend>(f: &mut Frame<B>, area: Rect) {
+fn render_status_bar<B: tui::backend::Backend>(f: &mut Frame<B>, app_state: &AppState, area: Rect) {
     // Updated help text reflects new keybindings
-    let help_text = " Quit: Ctrl+Q | Back: Ctrl+C | Nav: ↑/↓/j/k | Search: / Enter Esc | Scroll: PgUp/PgDn/Mouse ";
+    let help_text = " Quit: Ctrl+Q | Back: Ctrl+C | Nav: ↑/↓/j/k | Search: / Enter Esc | Scroll: PgUp/PgDn/Mouse | Keys: ? ";
+    let mut status_text = String::new();
+    if !app_state.crawl_control.is_expanding() {
+        status_text.push_str(" [Not expanding]");
+    }
+    if app_state.crawl_finished_at.is_none() && app_state.crawl_control.is_paused() {
+        match app_state.launch_config.pause_every {
+            Some(_) => status_text.push_str(&format!(
+                " [Paused for review ({} pages)]",
+                app_state.crawl_control.pages_crawled()
+            )),
+            None => status_text.push_str(" [Paused]"),
+        }
+    }
+    if app_state.follow_bottom {
+        status_text.push_str(" [Following bottom]");
+    }
+    if app_state.pin_selection_top {
+        status_text.push_str(" [Pinned to top]");
+    }
+    if let Some(host) = &app_state.domain_filter {
+        status_text.push_str(&format!(" [Only {}]", host));
+    }
+    if app_state.crawl_finished_at.is_some() {
+        status_text.push_str(" [Done]");
+    } else if app_state.worker_count > 1 {
+        status_text.push_str(&format!(" [{} workers]", app_state.worker_count));
+    }
+    if let Some((done, total)) = app_state.refresh_progress() {
+        status_text.push_str(&format!(" Refreshed {}/{} |", done, total));
+    }
+    if let Some(budget) = app_state.launch_config.max_requests {
+        status_text.push_str(&format!(
+            " Requests: {}/{} |",
+            app_state.crawl_control.requests_made(),
+            budget
+        ));
+    }
+    if let Some(time_left) = app_state.crawl_time_left() {
+        status_text.push_str(&format!(" Time left: {} |", format_duration(time_left)));
+    }
+    if app_state.discarded_pages > 0 {
+        status_text.push_str(&format!(" Not kept: {} |", app_state.discarded_pages));
+    }
+    if !app_state.evicted_urls.is_empty() {
+        status_text.push_str(&format!(" Evicted: {} |", app_state.evicted_urls.len()));
+    }
+    if let Some(crawler_status) = &app_state.crawler_status {
+        status_text.push_str(&format!(" {} |", crawler_status));
+    }
+    if let Some(heartbeat) = heartbeat_text(app_state) {
+        status_text.push_str(&heartbeat);
+    }
+    status_text.push_str(&format!(
+        " {} extracted |",
+        format_bytes(app_state.total_bytes)
+    ));
+    match &app_state.status_message {
+        Some(message) => status_text.push_str(&format!(" {} ", message)),
+        None => status_text.push_str(help_text),
+    }
     let status_widget =
-        Paragraph::new(help_text).style(Style::default().bg(Color::DarkGray).fg(Color::White));
+        Paragraph::new(status_text).style(Style::default().bg(Color::DarkGray).fg(Color::White));
     f.render_widget(status_widget, area);
 }
 
+// Quiet this long and the heartbeat starts counting, so a slow page doesn't look like a hang
+const HEARTBEAT_IDLE_AFTER: Duration = Duration::from_secs(3);
+const HEARTBEAT_BLINK_TICKS: usize = 5; // Main loop passes per dot phase, ~0.5s when idle
+
+// " ● |" while the crawl runs, " ○ waiting 12s |" once nothing has arrived for a while
+fn heartbeat_text(app_state: &AppState) -> Option<String> {
+    // A paused crawl is quiet on purpose
+    if !app_state.heartbeat
+        || app_state.crawl_finished_at.is_some()
+        || app_state.crawl_control.is_paused()
+    {
+        return None;
+    }
+    let dot = if (app_state.poll_ticks / HEARTBEAT_BLINK_TICKS).is_multiple_of(2) {
+        '●'
+    } else {
+        '○'
+    };
+    let quiet = app_state.last_crawler_message.elapsed();
+    Some(if quiet >= HEARTBEAT_IDLE_AFTER {
+        format!(" {} waiting {}s |", dot, quiet.as_secs())
+    } else {
+        format!(" {} |", dot)
+    })
+}
+
 // --- Event Handling ---
 
 enum AppControl {
@@ -493,21 +7138,82 @@ enum AppControl {
 
 // Handles key events specifically
 fn handle_key_input(key: KeyEvent, app_state: &mut AppState) -> AppControl {
-    if app_state.is_searching {
+    app_state.status_message = None;
+    match app_state.popup {
+        Some(Popup::Links) => {
+            handle_links_key(key, app_state);
+            return AppControl::Continue;
+        }
+        Some(Popup::Queue) => {
+            handle_queue_key(key, app_state);
+            return AppControl::Continue;
+        }
+        Some(Popup::RecentQueries) => {
+            handle_recent_queries_key(key, app_state);
+            return AppControl::Continue;
+        }
+        Some(Popup::Baseline) => {
+            handle_baseline_key(key, app_state);
+            return AppControl::Continue;
+        }
+        Some(popup @ (Popup::StructuredData | Popup::Harvest)) => {
+            handle_scrolling_popup_key(key, app_state, popup);
+            return AppControl::Continue;
+        }
+        Some(_) => {
+            app_state.popup = None; // Any key dismisses an informational popup
+            return AppControl::Continue;
+        }
+        None => {}
+    }
+
+    if let Some(input) = app_state.profile_input.as_mut() {
         match key.code {
-            KeyCode::Enter => app_state.finalize_search(),
-            KeyCode::Char(c) => app_state.search_input.push(c),
-            KeyCode::Backspace => {
-                app_state.search_input.pop();
+            KeyCode::Enter => app_state.save_profile(),
+            KeyCode::Esc => app_state.profile_input = None,
+            KeyCode::Char(c) if !(c.is_ascii_alphanumeric() || "-_.".contains(c)) => {}
+            _ => {
+                input.handle_key(&key);
             }
+        }
+    } else if let Some(input) = app_state.line_input.as_mut() {
+        match key.code {
+            KeyCode::Enter => app_state.jump_to_line(),
+            KeyCode::Esc => app_state.line_input = None,
+            KeyCode::Char(c) if !c.is_ascii_digit() => {}
+            _ => {
+                input.handle_key(&key);
+            }
+        }
+    } else if app_state.is_searching {
+        match key.code {
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.show_recent_queries()
+            }
+            KeyCode::Enter => app_state.finalize_search(),
             KeyCode::Esc => app_state.cancel_search(),
-            _ => {}
+            _ => {
+                app_state.search_input.handle_key(&key);
+            }
         }
     } else {
         match key.code {
             // Navigation
+            KeyCode::Tab => app_state.toggle_focus(),
+            KeyCode::Down | KeyCode::Char('j') if app_state.focus == Focus::Content => {
+                app_state.move_content_cursor_down()
+            }
+            KeyCode::Up | KeyCode::Char('k') if app_state.focus == Focus::Content => {
+                app_state.move_content_cursor_up()
+            }
             KeyCode::Down | KeyCode::Char('j') => app_state.select_next(),
             KeyCode::Up | KeyCode::Char('k') => app_state.select_previous(),
+            KeyCode::Home => app_state.select_seed(),
+            KeyCode::Backspace => app_state.select_parent(),
+            KeyCode::Char(':') => app_state.start_line_input(),
+            KeyCode::Char('K') => app_state.start_profile_input(),
+            KeyCode::Char(']') => app_state.select_error_page(true),
+            KeyCode::Char('[') => app_state.select_error_page(false),
 
             // Content Scrolling
             KeyCode::PageDown => app_state.scroll_content_down(SCROLL_LINES),
@@ -521,11 +7227,87 @@ fn handle_key_input(key: KeyEvent, app_state: &mut AppState) -> AppControl {
 
             // Search
             KeyCode::Char('/') => app_state.start_search(),
-            KeyCode::Esc => {
-                if !app_state.active_search_query.is_empty() {
-                    app_state.clear_search();
-                }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.show_recent_queries()
+            }
+            KeyCode::Char('n') => app_state.jump_to_match(true),
+            KeyCode::Char('N') => app_state.jump_to_match(false),
+            KeyCode::Char('s') => app_state.cycle_search_scope(),
+            KeyCode::Char('o') => app_state.toggle_whole_word(),
+            KeyCode::Char('a') => app_state.cycle_term_mode(),
+            KeyCode::Char('g') => app_state.toggle_only_matches(),
+            KeyCode::Esc if app_state.selection_anchor.is_some() => {
+                app_state.selection_anchor = None
+            }
+            KeyCode::Esc if !app_state.active_search_query.is_empty() => {
+                app_state.clear_search();
+            }
+
+            // Filters
+            KeyCode::Char('+') => app_state.adjust_min_body_chars(true),
+            KeyCode::Char('-') => app_state.adjust_min_body_chars(false),
+
+            // Popups
+            KeyCode::Char('e') => app_state.show_full_url(),
+            KeyCode::Char('?') => app_state.popup = Some(Popup::Help),
+            KeyCode::Char('M') => app_state.show_harvest(),
+            KeyCode::Char('i') => app_state.show_info(),
+            KeyCode::Char('E') => app_state.focus_next_error(),
+            KeyCode::Char('l') => app_state.show_links(),
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.toggle_pin()
+            }
+            KeyCode::Char('p') => app_state.show_queue(),
+            KeyCode::Char('B') => app_state.show_baseline(),
+            KeyCode::Char('J') => app_state.show_structured_data(),
+
+            // View
+            KeyCode::Char('F') => app_state.toggle_follow_bottom(),
+            KeyCode::Char('U') => app_state.toggle_final_urls(),
+            KeyCode::Char('t') => app_state.toggle_time_display(),
+            KeyCode::Char('P') => app_state.toggle_pin_selection_top(),
+            KeyCode::Char('H') => app_state.toggle_depth_chart(),
+            KeyCode::Char('D') => app_state.toggle_domains_panel(),
+            KeyCode::Char('h') => app_state.toggle_domain_filter(),
+            KeyCode::Char('r') => app_state.toggle_list_order(),
+            KeyCode::Char('T') => app_state.toggle_slowest_first(),
+            KeyCode::Char('V') => app_state.toggle_view_mode(),
+            KeyCode::Enter => app_state.toggle_content_group(),
+            KeyCode::Char('w') => app_state.toggle_wrap(),
+            KeyCode::Char('W') => app_state.toggle_clean_view(),
+            KeyCode::Char('z') => app_state.toggle_collapse_repeats(),
+            KeyCode::Char('G') => app_state.toggle_dim_boilerplate(),
+            KeyCode::Char('L') => app_state.toggle_full_url_line(),
+            KeyCode::Char('X') => app_state.export_snapshot(),
+            KeyCode::F(2) => app_state.show_search_bar = !app_state.show_search_bar,
+            KeyCode::F(3) => app_state.show_status_bar = !app_state.show_status_bar,
+            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.toggle_url_list()
+            }
+            KeyCode::Char('Z') => app_state.toggle_fullscreen_content(),
+
+            // Clipboard
+            KeyCode::Char('Y') => app_state.yank_cursor_line(),
+            KeyCode::Char('v') if app_state.focus == Focus::Content => {
+                app_state.toggle_line_selection()
+            }
+            KeyCode::Char('y') if app_state.selection_anchor.is_some() => {
+                app_state.yank_selection()
             }
+            KeyCode::Char('y') => app_state.yank_cursor_line(),
+            KeyCode::Char('O') => app_state.copy_page_html(),
+            KeyCode::Char('S') => app_state.write_sitemap(),
+            KeyCode::Char('c') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app_state.copy_command_line()
+            }
+
+            // Crawl Control
+            KeyCode::Char('x') => app_state.toggle_link_expansion(),
+            KeyCode::Char(' ') => app_state.toggle_pause(),
+            KeyCode::Char('R') => app_state.refresh_all(),
+            KeyCode::Char('A') => app_state.requeue_frontier(),
+            KeyCode::Char('>') => app_state.worker_request = Some(WorkerRequest::Add),
+            KeyCode::Char('<') => app_state.worker_request = Some(WorkerRequest::Stop),
 
             // Application Control (UPDATED)
             KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -541,6 +7323,74 @@ fn handle_key_input(key: KeyEvent, app_state: &mut AppState) -> AppControl {
     AppControl::Continue
 }
 
+fn handle_links_key(key: KeyEvent, app_state: &mut AppState) {
+    match key.code {
+        KeyCode::Down | KeyCode::Char('j') => app_state.select_next_link(),
+        KeyCode::Up | KeyCode::Char('k') => app_state.select_previous_link(),
+        KeyCode::Enter => app_state.follow_selected_link(),
+        KeyCode::Char('w') => app_state.export_outbound_links(false),
+        KeyCode::Char('y') => app_state.export_outbound_links(true),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('l') => app_state.popup = None,
+        _ => {}
+    }
+}
+
+fn handle_recent_queries_key(key: KeyEvent, app_state: &mut AppState) {
+    match key.code {
+        KeyCode::Down | KeyCode::Char('j') => app_state.select_next_query(),
+        KeyCode::Up | KeyCode::Char('k') => app_state.select_previous_query(),
+        KeyCode::Enter => app_state.apply_selected_query(),
+        KeyCode::Esc | KeyCode::Char('q') => app_state.popup = None,
+        _ => {}
+    }
+}
+
+fn handle_baseline_key(key: KeyEvent, app_state: &mut AppState) {
+    match key.code {
+        KeyCode::Down | KeyCode::Char('j') => app_state.select_next_baseline_entry(),
+        KeyCode::Up | KeyCode::Char('k') => app_state.select_previous_baseline_entry(),
+        KeyCode::Enter => app_state.show_selected_baseline_entry(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('B') => app_state.popup = None,
+        _ => {}
+    }
+}
+
+fn handle_queue_key(key: KeyEvent, app_state: &mut AppState) {
+    match key.code {
+        KeyCode::Down | KeyCode::Char('j') => app_state.select_next_queued(),
+        KeyCode::Up | KeyCode::Char('k') => app_state.select_previous_queued(),
+        KeyCode::Enter => app_state.prioritize_selected_queued(),
+        KeyCode::Char('d') | KeyCode::Delete => app_state.remove_selected_queued(),
+        KeyCode::Char('C') => app_state.clear_queue(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') => app_state.popup = None,
+        _ => {}
+    }
+}
+
+// Keys for the scrollable, exportable popups
+fn handle_scrolling_popup_key(key: KeyEvent, app_state: &mut AppState, popup: Popup) {
+    match key.code {
+        KeyCode::Down | KeyCode::Char('j') => {
+            app_state.popup_scroll = app_state.popup_scroll.saturating_add(1)
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app_state.popup_scroll = app_state.popup_scroll.saturating_sub(1)
+        }
+        KeyCode::PageDown => {
+            app_state.popup_scroll = app_state.popup_scroll.saturating_add(SCROLL_LINES * 5)
+        }
+        KeyCode::PageUp => {
+            app_state.popup_scroll = app_state.popup_scroll.saturating_sub(SCROLL_LINES * 5)
+        }
+        KeyCode::Char('w') if popup == Popup::Harvest => app_state.export_harvest(),
+        KeyCode::Char('w') => app_state.export_json_ld(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('J') | KeyCode::Char('M') => {
+            app_state.popup = None
+        }
+        _ => {}
+    }
+}
+
 // Handles mouse events specifically
 fn handle_mouse_input(mouse_event: MouseEvent, app_state: &mut AppState) {
     // Check if the mouse coordinates are within the content panel's area
@@ -566,14 +7416,24 @@ fn handle_mouse_input(mouse_event: MouseEvent, app_state: &mut AppState) {
 // --- Main Application Logic ---
 
 // Setup terminal with mouse capture enabled
-fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>, Box<dyn Error>> {
+fn setup_terminal(
+    config: &Config,
+) -> Result<Terminal<CrosstermBackend<io::Stdout>>, Box<dyn Error>> {
     let mut stdout = io::stdout();
     enable_raw_mode()?;
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?; // Enable mouse
+    update_terminal_title(config, "crabcrawl");
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend).map_err(|e| e.into())
 }
 
+// Not every terminal supports titles, so this only happens with --set-terminal-title
+fn update_terminal_title(config: &Config, title: &str) {
+    if config.set_terminal_title {
+        execute!(io::stdout(), SetTitle(title)).ok();
+    }
+}
+
 // RAII guard ensures mouse capture is disabled on exit
 struct RawModeGuard;
 impl Drop for RawModeGuard {
@@ -587,77 +7447,271 @@ impl Drop for RawModeGuard {
 // Prompt for URL - updated keybindings
 fn prompt_for_url<B: tui::backend::Backend>(
     terminal: &mut Terminal<B>,
+    last_url: Option<&Url>,
 ) -> Result<Option<Url>, Box<dyn Error>> {
-    let mut input_url = String::new();
+    let mut input_url = TextInput::default();
+    let mut error: Option<String> = None; // Why the last Enter was rejected
     loop {
         terminal.draw(|f| {
             let size = f.size();
             // Updated prompt help text
-            let prompt_text = format!(
-                "Enter URL to crawl (Esc: back, Ctrl+Q: quit): {}",
-                input_url
+            let rerun_hint = last_url
+                .map(|url| format!(", Ctrl+R: re-run {}", url))
+                .unwrap_or_default();
+            let prompt = format!(
+                "Enter URL to crawl (Esc: back, Ctrl+Q: quit{}): ",
+                rerun_hint
             );
+            let prompt_text = format!("{}{}", prompt, input_url.as_str());
+            let title = match &error {
+                Some(e) => format!("Start URL ({})", e),
+                None => "Start URL".to_string(),
+            };
             let paragraph = Paragraph::new(prompt_text)
-                .block(Block::default().borders(Borders::ALL).title("Start URL"));
+                .block(Block::default().borders(Borders::ALL).title(title));
             f.render_widget(paragraph, size);
+            let x = size.x + 1 + prompt.width().min(u16::MAX as usize) as u16;
+            place_input_cursor(f, size, x, size.y + 1, &input_url);
         })?;
 
-        if event::poll(Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char(c) if !(key.modifiers.contains(KeyModifiers::CONTROL)) => {
-                        input_url.push(c);
+        if event::poll(Duration::from_millis(200))?
+            && let Event::Key(key) = event::read()?
+        {
+            match key.code {
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Some(url) = last_url {
+                        return Ok(Some(url.clone())); // Ctrl+R re-runs the previous crawl
                     }
-                    KeyCode::Backspace => {
-                        input_url.pop();
+                }
+                KeyCode::Enter => match parse_seed_url(input_url.as_str()) {
+                    Ok(url) => {
+                        return Ok(Some(url));
                     }
-                    KeyCode::Enter => match Url::parse(&input_url) {
-                        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
-                            return Ok(Some(url));
-                        }
-                        _ => {
-                            input_url.clear();
-                        }
-                    },
-                    // Updated Quit Keys
-                    KeyCode::Esc => return Ok(None), // Esc just exits prompt -> back to main loop check
-                    KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        return Ok(None);
-                    } // Ctrl+Q exits app
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        return Ok(None);
-                    } // Ctrl+C exits app from prompt
-                    _ => {}
+                    // Keep the text so a typo can be fixed in place
+                    Err(e) => error = Some(e),
+                },
+                // Updated Quit Keys
+                KeyCode::Esc => return Ok(None), // Esc just exits prompt -> back to main loop check
+                KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(None);
+                } // Ctrl+Q exits app
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(None);
+                } // Ctrl+C exits app from prompt
+                _ => {
+                    input_url.handle_key(&key);
+                }
+            }
+        }
+    }
+}
+
+// Seeds the queue and spawns the crawler; shared by the TUI and the plain stdout front end
+async fn start_crawl(
+    base_url: &Url,
+    url_queue: UrlQueue,
+    visited: VisitedSet,
+    control: Arc<CrawlControl>,
+    config: Arc<Config>,
+    sessions: Arc<SessionPool>,
+) -> CrawlerPool {
+    url_queue.lock().await.push_back(QueuedUrl {
+        url: canonicalize_url(base_url.clone(), &config),
+        depth: 0,
+        parent: None,
+    });
+    CrawlerPool::new(base_url, url_queue, visited, control, config, sessions)
+}
+
+// --print: one "url<TAB>body" line per page on stdout, no TUI.
+// --cat: each page's body as is under a "==> url <==" header, for reading in a pager.
+async fn run_print_mode(
+    base_url: Url,
+    config: Arc<Config>,
+    mut result_stream: Option<ResultStream>,
+    metrics: Option<Arc<Metrics>>,
+) -> Result<(), Box<dyn Error>> {
+    let url_queue: UrlQueue = Arc::new(Mutex::new(VecDeque::new()));
+    let control = Arc::new(CrawlControl::new());
+    let visited = Arc::new(Mutex::new(HashSet::new()));
+    let sessions = Arc::new(SessionPool::new());
+    let started_at = Instant::now();
+    let mut rx = start_crawl(
+        &base_url,
+        url_queue.clone(),
+        visited,
+        control,
+        config.clone(),
+        sessions.clone(),
+    )
+    .await
+    .into_receiver();
+
+    let mut stdout = io::stdout().lock();
+    while let Some(message) = rx.recv().await {
+        if let Some(stream) = result_stream.as_mut()
+            && stream.write(&message).is_err()
+        {
+            eprintln!("Output stream closed, stopping the crawl");
+            break;
+        }
+        if let Some(metrics) = &metrics {
+            metrics.record(&message);
+            metrics.set_progress(url_queue.lock().await.len(), started_at.elapsed());
+        }
+        match message {
+            CrawlerMessage::Page(page) if config.cat => {
+                if is_placeholder_body(&page.body) {
+                    eprintln!("No text extracted from {}: {}", page.url, page.body);
+                    continue;
+                }
+                let written = writeln!(stdout, "==> {} <==", page.url)
+                    .and_then(|()| writeln!(stdout, "{}\n", page.body.trim_end()));
+                if written.is_err() {
+                    break; // The pager quit
+                }
+            }
+            CrawlerMessage::Page(page) => {
+                // Flatten the body so each page stays on one greppable line
+                let body = page.body.split_whitespace().collect::<Vec<_>>().join(" ");
+                if writeln!(stdout, "{}\t{}", page.url, body).is_err() {
+                    break; // Downstream closed the pipe (e.g. `| head`)
                 }
             }
+            CrawlerMessage::Failed { url, error } => {
+                eprintln!("Error crawling {}: {}", url, error)
+            }
+            CrawlerMessage::Status(Some(status)) => eprintln!("{}", status),
+            CrawlerMessage::Status(None)
+            | CrawlerMessage::Fetching(_)
+            | CrawlerMessage::Discarded(_) => {}
         }
     }
+    sessions.close_all().await;
+    Ok(())
 }
 
 // Main app loop - updated event handling
 async fn run_app<B: tui::backend::Backend>(
     terminal: &mut Terminal<B>,
     base_url: Url,
+    config: Arc<Config>,
+    session: Option<Session>,
+    result_stream: &mut Option<ResultStream>, // Outlives each crawl, like the terminal
+    metrics: Option<&Metrics>,
+    baseline: Option<&Baseline>,
 ) -> Result<AppControl, Box<dyn Error>> {
-    let mut app_state = AppState::new();
-    let visited = Arc::new(Mutex::new(HashSet::new()));
-    let url_queue = Arc::new(Mutex::new(VecDeque::from([base_url.to_string()])));
-    let (tx, mut rx) = mpsc::channel::<CrawlerMessage>(CRAWLER_CHANNEL_BUFFER);
-
-    let crawler_handle = tokio::spawn(crawler_task(
-        base_url.clone(),
-        tx,
-        url_queue.clone(),
-        visited.clone(),
-    ));
+    let mut app_state = AppState::new(&config, &base_url);
+    app_state.baseline = baseline.cloned();
+    // A resumed session has nothing left to crawl
+    let mut crawler = match session {
+        Some(session) => {
+            app_state.restore_session(session);
+            None
+        }
+        None => Some(
+            start_crawl(
+                &base_url,
+                app_state.url_queue.clone(),
+                app_state.visited.clone(),
+                app_state.crawl_control.clone(),
+                config.clone(),
+                app_state.sessions.clone(),
+            )
+            .await,
+        ),
+    };
+    let mut terminal_title = String::new();
 
     loop {
+        app_state.poll_ticks = app_state.poll_ticks.wrapping_add(1);
+        if app_state.popup == Some(Popup::Queue) {
+            app_state.refresh_queue_snapshot();
+        }
+
         // Draw UI - this now updates app_state.content_area
         terminal.draw(|f| ui(f, &mut app_state))?;
 
+        // A refresh after the crawler exited needs a new one to work through the queue
+        if app_state.restart_crawler && crawler.as_ref().is_none_or(CrawlerPool::is_finished) {
+            app_state.restart_crawler = false;
+            app_state.last_crawler_message = Instant::now();
+            crawler = Some(CrawlerPool::new(
+                &base_url,
+                app_state.url_queue.clone(),
+                app_state.visited.clone(),
+                app_state.crawl_control.clone(),
+                config.clone(),
+                app_state.sessions.clone(),
+            ));
+        }
+
         // Handle incoming crawler messages
-        while let Ok((url, body)) = rx.try_recv() {
-            app_state.add_crawl_result(url, body);
+        if let Some(pool) = crawler.as_mut() {
+            match app_state.worker_request.take() {
+                Some(WorkerRequest::Add) if config.deterministic => {
+                    app_state.status_message =
+                        Some("--deterministic crawls with a single worker".to_string());
+                }
+                Some(WorkerRequest::Add) => {
+                    pool.add_worker();
+                    let workers = pool.active_workers();
+                    app_state.status_message = Some(format!("{} crawler workers", workers));
+                }
+                Some(WorkerRequest::Stop) => {
+                    app_state.status_message = Some(if pool.stop_worker() {
+                        "Stopping a worker after its current page".to_string()
+                    } else {
+                        "Already down to one worker".to_string()
+                    });
+                }
+                None => {}
+            }
+            app_state.worker_count = pool.active_workers();
+
+            // Checked before draining so every message sent before the crawler exited gets handled
+            let crawler_finished = pool.is_finished();
+            while let Ok(message) = pool.rx.try_recv() {
+                app_state.last_crawler_message = Instant::now();
+                // The crawl goes on without the stream once its reader has gone
+                if let Some(stream) = result_stream.as_mut()
+                    && stream.write(&message).is_err()
+                {
+                    *result_stream = None;
+                    app_state.status_message =
+                        Some("Output stream closed, no longer streaming results".to_string());
+                }
+                if let Some(metrics) = metrics {
+                    metrics.record(&message);
+                }
+                match message {
+                    CrawlerMessage::Fetching(url) => app_state.add_loading_url(url),
+                    CrawlerMessage::Discarded(url) => app_state.discard_page(&url),
+                    CrawlerMessage::Page(page) => app_state.add_page(*page),
+                    CrawlerMessage::Failed { url, error } => {
+                        app_state.add_crawl_failure(url, error)
+                    }
+                    CrawlerMessage::Status(status) => app_state.crawler_status = status,
+                }
+            }
+            if crawler_finished && app_state.crawl_finished_at.is_none() {
+                app_state.finish_crawl(config.show_summary);
+            }
+            if let Some(metrics) = metrics
+                && let Ok(queue) = app_state.url_queue.try_lock()
+            {
+                metrics.set_progress(queue.len(), app_state.crawl_elapsed());
+            }
+        }
+
+        let title = format!(
+            "crabcrawl — crawling {} ({})",
+            base_url.host_str().unwrap_or_default(),
+            app_state.body_texts.len()
+        );
+        if title != terminal_title {
+            update_terminal_title(&config, &title);
+            terminal_title = title;
         }
 
         // Handle Input Events (Key and Mouse)
@@ -668,7 +7722,19 @@ async fn run_app<B: tui::backend::Backend>(
                     match handle_key_input(key_event, &mut app_state) {
                         AppControl::Continue => {} // Do nothing, continue loop
                         exit_command @ (AppControl::ExitCrawlerView | AppControl::ExitApp) => {
-                            crawler_handle.abort();
+                            if let Some(pool) = crawler {
+                                pool.abort();
+                                app_state.sessions.close_all().await;
+                                // Best effort: the terminal is about to be reused, so
+                                // there's nowhere useful to report a failed save
+                                if !app_state.body_texts.is_empty() {
+                                    save_session(&app_state.to_session(&base_url)).ok();
+                                }
+                                // Also covers a crawl that's left before it finishes
+                                if app_state.sitemap_out.is_some() {
+                                    app_state.write_sitemap();
+                                }
+                            }
                             return Ok(exit_command); // Return control signal
                         }
                     }
@@ -689,19 +7755,132 @@ async fn run_app<B: tui::backend::Backend>(
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let mut terminal = setup_terminal()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    // Validate options before touching the terminal so errors stay readable
+    let mut config =
+        match expand_profiles(args).and_then(|args| Config::from_args(args.into_iter())) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("error: {}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        };
+    // Read a piped seed now, before crossterm takes over the terminal
+    if config.seed_from_stdin {
+        match read_stdin_seed() {
+            Ok(url) => config.seed_url = Some(url),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+    let config = Arc::new(config);
+
+    // --resume / --continue-last open a saved session before the usual prompt
+    let resume_path = match (&config.resume, config.continue_last) {
+        (Some(path), _) => Some(path.clone()),
+        (None, true) => match latest_session_path() {
+            Some(path) => Some(path),
+            None => {
+                eprintln!("error: no saved sessions to continue");
+                std::process::exit(2);
+            }
+        },
+        (None, false) => None,
+    };
+    let mut startup_session = match resume_path.map(|path| load_session(&path)) {
+        Some(Ok(session)) => Some(session),
+        Some(Err(e)) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+        None => None,
+    };
+
+    let baseline = match config.baseline.as_deref().map(load_baseline) {
+        Some(Ok(baseline)) => Some(baseline),
+        Some(Err(e)) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+        None => None,
+    };
+
+    let mut result_stream = match &config.output_stream {
+        Some(path) => match ResultStream::open(path) {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                eprintln!("error: can't open {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    // Bound up front so a port that's taken is reported before the TUI starts
+    let metrics = match config.metrics_port {
+        Some(port) => match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => {
+                let metrics = Arc::new(Metrics::new());
+                tokio::spawn(serve_metrics(listener, metrics.clone()));
+                Some(metrics)
+            }
+            Err(e) => {
+                eprintln!("error: can't serve metrics on port {}: {}", port, e);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    if config.print || config.cat {
+        let mode = if config.cat { "--cat" } else { "--print" };
+        let Some(seed_url) = config.seed_url.clone() else {
+            eprintln!("error: {} needs a URL to crawl\n\n{}", mode, USAGE);
+            std::process::exit(2);
+        };
+        return run_print_mode(seed_url, config, result_stream, metrics).await;
+    }
+
+    let mut terminal = setup_terminal(&config)?;
     let _raw_mode_guard = RawModeGuard; // RAII guard ensures cleanup
+    let mut last_url: Option<Url> = None; // Seed of the previous crawl, offered for re-running
+    let mut startup_url = config.seed_url.clone(); // Skips the prompt for the first crawl only
 
     loop {
         terminal.clear()?;
+        update_terminal_title(&config, "crabcrawl");
 
-        let base_url = match prompt_for_url(&mut terminal)? {
+        let session = startup_session.take();
+        let resumed_url = session
+            .as_ref()
+            .and_then(|session| Url::parse(&session.seed_url).ok());
+        let base_url = match resumed_url.or_else(|| startup_url.take()) {
             Some(url) => url,
-            // If prompt_for_url returns None (Esc, Ctrl+Q, Ctrl+C), exit the app
-            None => break,
+            None => match prompt_for_url(&mut terminal, last_url.as_ref())? {
+                Some(url) => url,
+                // If prompt_for_url returns None (Esc, Ctrl+Q, Ctrl+C), exit the app
+                None => break,
+            },
         };
+        last_url = Some(base_url.clone());
 
-        match run_app(&mut terminal, base_url).await? {
+        match run_app(
+            &mut terminal,
+            base_url,
+            config.clone(),
+            session,
+            &mut result_stream,
+            metrics.as_deref(),
+            baseline.as_ref(),
+        )
+        .await?
+        {
             AppControl::ExitCrawlerView => continue, // Loop back to prompt_for_url
             AppControl::ExitApp => break,            // Exit the program entirely
             AppControl::Continue => unreachable!(),
@@ -710,3 +7889,229 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_query(url: &str) -> String {
+        let mut url = Url::parse(url).unwrap();
+        sort_query_params(&mut url);
+        url.to_string()
+    }
+
+    fn crawled_page(url: &str, body: &str) -> CrawledPage {
+        CrawledPage {
+            url: url.to_string(),
+            title: String::new(),
+            crawled_at: 0,
+            load_ms: 0,
+            final_url: None,
+            parent: None,
+            content_selector: None,
+            depth: 0,
+            body: body.to_string(),
+            links: Vec::new(),
+            json_ld: Vec::new(),
+            anchors: Vec::new(),
+            html: None,
+        }
+    }
+
+    #[test]
+    fn selection_stays_put_as_pages_arrive_under_a_filter() {
+        let seed = Url::parse("https://site.com/").unwrap();
+        let mut app_state = AppState::new(&Config::default(), &seed);
+        app_state.add_page(crawled_page("https://site.com/", "all about rust"));
+        app_state.add_page(crawled_page("https://site.com/a", "more rust"));
+        app_state.search_input.set("rust".to_string());
+        app_state.finalize_search();
+        assert!(app_state.select_url("https://site.com/a"));
+
+        // Matching and non-matching pages arrive, and a listed page is re-crawled
+        app_state.add_page(crawled_page("https://site.com/b", "rust again"));
+        app_state.add_page(crawled_page("https://site.com/c", "nothing here"));
+        app_state.add_page(crawled_page("https://site.com/", "rust, updated"));
+
+        assert_eq!(app_state.get_selected_url_str(), Some("https://site.com/a"));
+        assert_eq!(app_state.get_displayed_urls().len(), 3);
+    }
+
+    #[test]
+    fn failed_recrawl_that_keeps_its_body_counts_as_an_error() {
+        let seed = Url::parse("https://site.com/").unwrap();
+        let mut app_state = AppState::new(&Config::default(), &seed);
+        app_state.add_page(crawled_page("https://site.com/", "home"));
+        app_state.add_page(crawled_page("https://site.com/a", "page a"));
+        let timeout = || CrawlError {
+            kind: CrawlErrorKind::Timeout,
+            message: "timed out".to_string(),
+        };
+        app_state.add_crawl_failure("https://site.com/a".to_string(), timeout());
+        app_state.add_crawl_failure("https://site.com/b".to_string(), timeout());
+
+        assert_eq!(app_state.error_count(), 2);
+    }
+
+    #[test]
+    fn colors_parse_by_name_or_hex() {
+        let parse = |value| parse_color(value, "--highlight-color");
+        assert_eq!(parse("Light-Blue"), Ok(Color::LightBlue));
+        assert_eq!(parse("dark_grey"), Ok(Color::DarkGray));
+        assert_eq!(parse("#FF8000"), Ok(Color::Rgb(255, 128, 0)));
+        for bad in ["purple", "#ff80", "#gg8000", "#fé800", ""] {
+            assert!(parse(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn scope_domains_are_normalized() {
+        assert_eq!(
+            parse_scope_domain("Docs.Site.com"),
+            Ok("docs.site.com".to_string())
+        );
+        assert_eq!(
+            parse_scope_domain("*.site.com"),
+            Ok(".site.com".to_string())
+        );
+        assert_eq!(parse_scope_domain(".site.com"), Ok(".site.com".to_string()));
+        for bad in [
+            "",
+            "*.",
+            "site.com/docs",
+            "site.com:8080",
+            "*.*.site.com",
+            "a b.com",
+        ] {
+            assert!(parse_scope_domain(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn cli_args_rebuild_the_same_config() {
+        let args = [
+            "--url",
+            "https://site.com/docs/",
+            "--user-agent",
+            "Bot's UA 1.0",
+            "--sessions",
+            "3",
+            "--scope-domain",
+            "*.site.com",
+            "--highlight-color",
+            "#ff8000",
+            "--output-fd",
+            "3",
+            "--shuffle",
+            "--dim-boilerplate",
+        ];
+        let config = Config::from_args(args.iter().map(|arg| arg.to_string())).unwrap();
+        let written = config.to_cli_args();
+        let reparsed = Config::from_args(written.clone().into_iter()).unwrap();
+        assert_eq!(reparsed.to_cli_args(), written);
+        assert!(written.windows(2).any(|pair| pair == ["--output-fd", "3"]));
+        assert!(
+            written
+                .windows(2)
+                .any(|pair| pair == ["--user-agent", "Bot's UA 1.0"])
+        );
+        assert_eq!(shell_quote("Bot's UA 1.0"), r"'Bot'\''s UA 1.0'");
+    }
+
+    #[test]
+    fn deterministic_links_queue_in_the_same_order() {
+        let queued = |links: &[&str]| {
+            let mut queue = VecDeque::new();
+            let links = links.iter().map(|link| link.to_string()).collect();
+            let wanted = |link: &str| !link.ends_with("/skip");
+            enqueue_links(
+                &mut queue,
+                links,
+                None,
+                true,
+                wanted,
+                1,
+                "https://site.com/",
+            );
+            queue
+                .into_iter()
+                .map(|queued| queued.url)
+                .collect::<Vec<_>>()
+        };
+        let first = queued(&[
+            "https://site.com/b",
+            "https://site.com/skip",
+            "https://site.com/a",
+            "https://site.com/c",
+            "https://site.com/a",
+        ]);
+        let second = queued(&[
+            "https://site.com/c",
+            "https://site.com/a",
+            "https://site.com/b",
+            "https://site.com/skip",
+        ]);
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            [
+                "https://site.com/a",
+                "https://site.com/b",
+                "https://site.com/c"
+            ]
+        );
+    }
+
+    #[test]
+    fn non_page_hrefs_are_not_navigable() {
+        let base = Url::parse("https://site.com/docs/").unwrap();
+        for href in [
+            "mailto:me@site.com",
+            "tel:+15550100",
+            "javascript:void(0)",
+            "#section",
+            "  #top",
+        ] {
+            assert_eq!(navigable_link(&base, href, false), None, "{}", href);
+        }
+    }
+
+    #[test]
+    fn http_links_are_navigable_without_their_fragment() {
+        let base = Url::parse("https://site.com/docs/").unwrap();
+        let link = |href| navigable_link(&base, href, false).map(|url| url.to_string());
+        assert_eq!(
+            link("guide#intro").as_deref(),
+            Some("https://site.com/docs/guide")
+        );
+        assert_eq!(
+            link("http://other.com/").as_deref(),
+            Some("http://other.com/")
+        );
+        assert_eq!(link("/about").as_deref(), Some("https://site.com/about"));
+    }
+
+    #[test]
+    fn reordered_query_params_sort_the_same() {
+        assert_eq!(
+            sorted_query("https://site.com/p?b=2&a=1&c=3"),
+            sorted_query("https://site.com/p?c=3&a=1&b=2")
+        );
+        assert_eq!(
+            sorted_query("https://site.com/p?b=2&a=1"),
+            "https://site.com/p?a=1&b=2"
+        );
+    }
+
+    #[test]
+    fn repeated_query_keys_keep_their_value_order() {
+        assert_eq!(
+            sorted_query("https://site.com/p?b=1&a=2&a=1"),
+            "https://site.com/p?a=2&a=1&b=1"
+        );
+        assert_ne!(
+            sorted_query("https://site.com/p?a=2&a=1"),
+            sorted_query("https://site.com/p?a=1&a=2")
+        );
+    }
+}